@@ -1,6 +1,6 @@
 import test from "ava";
 
-import { readData } from "../index.js";
+import { isReady, readData } from "../index.js";
 
 test("readData from native", (t) => {
   let result = readData("MTGA", []);
@@ -9,3 +9,6 @@ test("readData from native", (t) => {
   });
 });
 
+test("isReady without the game", (t) => {
+  t.false(isReady("MTGA"));
+});
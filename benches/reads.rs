@@ -0,0 +1,90 @@
+// Read throughput of the parsing over fake Mono structures laid out in our
+// own memory, a baseline for the batch read and caching work. Throughput is
+// reported in reads, run with: cargo bench --features fake-mono
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use mtga_reader::fake_mono::{fake_array, FakeClass};
+use mtga_reader::managed::Managed;
+use mtga_reader::mono_reader::MonoReader;
+use mtga_reader::type_code::TypeCode;
+use mtga_reader::type_definition::TypeDefinition;
+
+const DICTIONARY_ENTRIES: usize = 1000;
+
+// Reads done by one call of read, as counted by MonoReader::stats
+fn reads_per_call(read: &impl Fn()) -> u64 {
+    let before = MonoReader::stats().reads;
+    read();
+    MonoReader::stats().reads - before
+}
+
+fn bench_instance(c: &mut Criterion) {
+    let class = FakeClass::new(
+        "Card",
+        &[
+            ("grpId", TypeCode::I4 as u8, 0x10),
+            ("count", TypeCode::I4 as u8, 0x14),
+            ("isRebalanced", TypeCode::BOOLEAN as u8, 0x18),
+        ],
+    );
+    let object: [usize; 4] = [class.vtable(), 0, 70123 | 4 << 32, 1];
+
+    let reader = MonoReader::new(std::process::id());
+    let read = || {
+        let mut definition = TypeDefinition::new(class.address(), &reader);
+        definition.set_fields_base(object.as_ptr() as usize);
+        black_box(definition.to_string());
+    };
+
+    let mut group = c.benchmark_group("instance");
+    group.throughput(Throughput::Elements(reads_per_call(&read)));
+    group.bench_function("read_class", |b| b.iter(&read));
+    group.finish();
+}
+
+fn bench_dictionary(c: &mut Criterion) {
+    let class = FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+            ("_freeList", TypeCode::I4 as u8, 0x24),
+            ("_freeCount", TypeCode::I4 as u8, 0x28),
+        ],
+    );
+    let entries_class = FakeClass::new("Entry[]", &[]).element_size(16);
+
+    // Entry<int, int>: hashCode, next, key, value
+    let entry_bytes: Vec<u8> = (0..DICTIONARY_ENTRIES as i32)
+        .flat_map(|key| [key, -1, key, key * 2])
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    let mut array = fake_array(DICTIONARY_ENTRIES, &entry_bytes);
+    array[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+
+    // object header, _buckets, _entries, _count and an empty _freeList,
+    // _freeCount
+    let dictionary: [usize; 6] = [
+        class.vtable(),
+        0,
+        0,
+        array.as_ptr() as usize,
+        DICTIONARY_ENTRIES | (u32::MAX as usize) << 32,
+        0,
+    ];
+    let location = [dictionary.as_ptr() as usize];
+
+    let reader = MonoReader::new(std::process::id());
+    let read = || {
+        let managed = Managed::new(&reader, location.as_ptr() as usize, None);
+        black_box(managed.read_dict_entries(None));
+    };
+
+    let mut group = c.benchmark_group("dictionary");
+    group.throughput(Throughput::Elements(reads_per_call(&read)));
+    group.bench_function("read_dict_entries", |b| b.iter(&read));
+    group.finish();
+}
+
+criterion_group!(benches, bench_instance, bench_dictionary);
+criterion_main!(benches);
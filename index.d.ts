@@ -3,8 +3,46 @@
 
 /* auto-generated by NAPI-RS */
 
-export function readData(processName: string, fields: Array<string>): any
-export function readClass(processName: string, address: number): any
+export function readData(processName: string, fields: Array<string>, strict?: boolean | undefined | null): any
+export function readDataByPid(pid: number, fields: Array<string>, strict?: boolean | undefined | null): any
+export function readDataTraced(processName: string, fields: Array<string>): any
+export function resolveFromRoot(processName: string, fields: Array<string>): any
+export function readClass(processName: string, address: number, maxFields?: number | undefined | null, skipPointers?: boolean | undefined | null, strict?: boolean | undefined | null): any
+export function readClassExpanded(processName: string, address: number, maxEntries?: number | undefined | null, strict?: boolean | undefined | null): any
 export function readGenericInstance(processName: string, address: number): any
+export function readProperty(processName: string, address: number, propertyName: string): any
+export function readRaw(processName: string, address: number, length: number): Buffer
+export function readFields(processName: string, address: number, fieldNames: Array<string>): any
+export function readList(processName: string, address: number, depth?: number | undefined | null): any
+export function readDeck(processName: string, address: number): any
+export function readDictionaryTyped(processName: string, address: number, keyType: string, valueType: string, depth?: number | undefined | null, strict?: boolean | undefined | null): any
+export function readDictionaryStream(processName: string, address: number, callback: (...args: any[]) => any): void
+export function getAssembliesDetailed(processName: string): any
+export function getAssemblyClasses(processName: string, assemblyName: string, offset?: number | undefined | null, limit?: number | undefined | null, namespace?: string | undefined | null, nameFilter?: string | undefined | null): any
+export function findSingletons(processName: string, assemblyName: string): any
+export function searchFields(processName: string, address: number, name: string, maxDepth?: number | undefined | null): any
+export function getClassDetails(processName: string, className: string, namespace?: string | undefined | null): any
+export function getFieldTypeInfo(processName: string, classAddress: number, fieldName: string): any
+export function exportCollection(processName: string): any
+export function readAccount(processName: string): any
+export function open(processName: string): ReaderHandle
+export function openByPid(pid: number): ReaderHandle
+export class ReaderHandle {
+  pid(): number
+  readData(fields: Array<string>, strict?: boolean | undefined | null): any
+  resolveFromRoot(fields: Array<string>): any
+  readClass(address: number, maxFields?: number | undefined | null, skipPointers?: boolean | undefined | null, strict?: boolean | undefined | null): any
+  readFields(address: number, fieldNames: Array<string>): any
+  readList(address: number, depth?: number | undefined | null): any
+}
+export class CardCollectionWatcher {
+  constructor(processName: string)
+  poll(): any
+}
 export function findPidByName(processName: string): boolean
+export function getReadStats(): any
+export function getRuntimeInfo(processName: string): any
+export function calibrateFieldOffsets(processName: string, className: string, expectedFields: Array<string>): any
+export function checkFieldCount(processName: string, className: string): any
+export function isReady(processName: string): boolean
 export function isAdmin(): boolean
@@ -310,10 +310,38 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { readData, readClass, readGenericInstance, findPidByName, isAdmin } = nativeBinding
+const { readData, readDataByPid, readDataTraced, resolveFromRoot, readClass, readClassExpanded, readGenericInstance, readProperty, readRaw, readFields, readList, readDeck, readDictionaryTyped, readDictionaryStream, getAssembliesDetailed, getAssemblyClasses, findSingletons, searchFields, getClassDetails, getFieldTypeInfo, exportCollection, readAccount, open, openByPid, ReaderHandle, CardCollectionWatcher, findPidByName, getReadStats, getRuntimeInfo, calibrateFieldOffsets, checkFieldCount, isReady, isAdmin } = nativeBinding
 
 module.exports.readData = readData
+module.exports.readDataByPid = readDataByPid
+module.exports.readDataTraced = readDataTraced
+module.exports.resolveFromRoot = resolveFromRoot
 module.exports.readClass = readClass
+module.exports.readClassExpanded = readClassExpanded
 module.exports.readGenericInstance = readGenericInstance
+module.exports.readProperty = readProperty
+module.exports.readRaw = readRaw
+module.exports.readFields = readFields
+module.exports.readList = readList
+module.exports.readDeck = readDeck
+module.exports.readDictionaryTyped = readDictionaryTyped
+module.exports.readDictionaryStream = readDictionaryStream
+module.exports.getAssembliesDetailed = getAssembliesDetailed
+module.exports.getAssemblyClasses = getAssemblyClasses
+module.exports.findSingletons = findSingletons
+module.exports.searchFields = searchFields
+module.exports.getClassDetails = getClassDetails
+module.exports.getFieldTypeInfo = getFieldTypeInfo
+module.exports.exportCollection = exportCollection
+module.exports.readAccount = readAccount
+module.exports.open = open
+module.exports.openByPid = openByPid
+module.exports.ReaderHandle = ReaderHandle
+module.exports.CardCollectionWatcher = CardCollectionWatcher
 module.exports.findPidByName = findPidByName
+module.exports.getReadStats = getReadStats
+module.exports.getRuntimeInfo = getRuntimeInfo
+module.exports.calibrateFieldOffsets = calibrateFieldOffsets
+module.exports.checkFieldCount = checkFieldCount
+module.exports.isReady = isReady
 module.exports.isAdmin = isAdmin
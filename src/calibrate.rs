@@ -0,0 +1,117 @@
+// Finds the MonoClass offsets of the field list and field count for a new
+// Unity build. Give it a class found by name (the name offsets have to be
+// right already) and some of its field names in declaration order, it tries
+// every offset of a window until reading the class gives those names.
+use crate::mono_reader::is_plausible_class_name;
+use crate::{constants, MonoReader};
+
+// Window of the MonoClass searched, both offsets moved within it so far
+const SEARCH_START: usize = 0x60;
+const SEARCH_END: usize = 0x140;
+
+// Longest field list walked for a candidate
+const MAX_FIELDS: usize = 1024;
+
+#[derive(Debug, PartialEq)]
+pub struct FieldOffsets {
+    pub fields: usize,
+    pub field_count: usize,
+    // other field count offsets that matched, to check by hand
+    pub alternatives: Vec<usize>,
+}
+
+impl FieldOffsets {
+    // Ready to paste over constants.rs
+    pub fn profile(&self) -> String {
+        format!(
+            "pub const TYPE_DEFINITION_FIELDS: u32 = {:#x};\npub const TYPE_DEFINITION_FIELD_COUNT: u32 = {:#x};\n",
+            self.fields, self.field_count
+        )
+    }
+}
+
+pub fn find_field_offsets(
+    reader: &MonoReader,
+    class: usize,
+    expected_fields: &[String],
+) -> Option<FieldOffsets> {
+    if expected_fields.is_empty() {
+        return None;
+    }
+
+    for fields in (SEARCH_START..SEARCH_END).step_by(constants::SIZE_OF_PTR) {
+        let first_field = match reader.maybe_read_ptr(class + fields) {
+            Some(ptr) if ptr != 0 => ptr,
+            _ => continue,
+        };
+
+        let names = read_field_names(reader, first_field);
+        let needed = match expected_prefix_length(&names, expected_fields) {
+            Some(needed) => needed,
+            None => continue,
+        };
+
+        // the count has to cover the expected names and no garbage after the
+        // list, the exact length of the list is the best guess
+        let mut counts: Vec<usize> = (SEARCH_START..SEARCH_END)
+            .step_by(4)
+            .filter(|offset| {
+                let count = reader.maybe_read_u32(class + offset).unwrap_or(0) as usize;
+                count >= needed && count <= names.len()
+            })
+            .collect();
+        counts.sort_by_key(|offset| {
+            reader.maybe_read_u32(class + offset).unwrap_or(0) as usize != names.len()
+        });
+
+        if counts.is_empty() {
+            continue;
+        }
+
+        let field_count = counts.remove(0);
+        return Some(FieldOffsets {
+            fields,
+            field_count,
+            alternatives: counts,
+        });
+    }
+
+    None
+}
+
+// Names of the MonoClassField list, up to the first entry without a
+// plausible name
+fn read_field_names(reader: &MonoReader, first_field: usize) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for index in 0..MAX_FIELDS {
+        let field = first_field + index * constants::TYPE_DEFINITION_FIELD_SIZE as usize;
+        let name = reader
+            .maybe_read_ptr(field + constants::SIZE_OF_PTR)
+            .filter(|ptr| *ptr != 0)
+            .and_then(|ptr| reader.maybe_read_ascii_string(ptr));
+
+        match name {
+            Some(name) if is_plausible_class_name(&name) => names.push(name),
+            _ => break,
+        }
+    }
+
+    names
+}
+
+// Number of fields to read to see all the expected names in order
+fn expected_prefix_length(names: &[String], expected_fields: &[String]) -> Option<usize> {
+    let mut expected = expected_fields.iter().peekable();
+
+    for (index, name) in names.iter().enumerate() {
+        if expected.peek() == Some(&name) {
+            expected.next();
+            if expected.peek().is_none() {
+                return Some(index + 1);
+            }
+        }
+    }
+
+    None
+}
@@ -1,9 +1,72 @@
 pub const RIP_PLUS_OFFSET_OFFSET: usize = 0x3;
 pub const RIP_VALUE_OFFSET: usize = 0x7;
 
+// Unity build and runtime the offsets below were taken from
+pub const OFFSETS_PROFILE: &str = "2021.3.14f1 mono x64";
+pub const RUNTIME: &str = "mono";
+
+// This file is the only place offsets live, every reader uses it. Only the
+// object, array and string layouts and a few MonoType fields are derived
+// from SIZE_OF_PTR. The _MonoImage, _MonoClass, _MonoDomain and MonoVTable
+// offsets are plain x64 values and don't follow it: a 32 bit build needs a
+// whole new set (calibrate_field_offsets finds the field list ones).
 pub const SIZE_OF_PTR: usize = 8; // for 32 bit it's 4
 
+// upper bound for raw memory reads requested from JS
+pub const MAX_RAW_READ_LENGTH: usize = 0x10000;
+
+// upper bound of entries expanded from a static array/list of instances
+pub const MAX_STATIC_INSTANCES: u32 = 64;
+
+// upper bound of array/list elements expanded into nested objects
+pub const MAX_EXPANDED_ELEMENTS: u32 = 1000;
+
+// MonoObject: vtable, synchronisation. Instance field offsets of a class
+// count it, so a field is at object + offset. Value types stored inline (in
+// a field, an array or a box payload) have no header, their fields are at
+// value + offset - OBJECT_HEADER_SIZE.
+pub const OBJECT_HEADER_SIZE: usize = SIZE_OF_PTR * 2;
+
+// MonoArray, after the object header
+// bounds (null for single dimension arrays)
+pub const ARRAY_BOUNDS: usize = OBJECT_HEADER_SIZE;
+// max_length, the element count of all the dimensions
+pub const ARRAY_MAX_LENGTH: usize = ARRAY_BOUNDS + SIZE_OF_PTR;
+// elements start right after the header
+pub const ARRAY_HEADER_SIZE: usize = OBJECT_HEADER_SIZE + SIZE_OF_PTR * 2;
+
+// elements are read in pointer sized steps from the end of the header
+const _: () = assert!(ARRAY_HEADER_SIZE.is_multiple_of(SIZE_OF_PTR));
+
+// Dictionary<TKey, TValue>.Entry with 4 byte key and value
+// hashCode, next, key, value
+pub const DICTIONARY_ENTRY_SIZE: usize = 0x10;
+
+// entries per callback of read_dictionary_stream
+pub const DICTIONARY_STREAM_BATCH: u32 = 500;
+
+// levels of dictionaries held as values read as pairs, and the entries read
+// over all the inner dictionaries
+pub const MAX_NESTED_DICTIONARY_DEPTH: u32 = 3;
+pub const MAX_NESTED_DICTIONARY_ENTRIES: u32 = 100_000;
+
+// Number.MAX_SAFE_INTEGER, integers above it lose precision in JS
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
 pub const MONO_LIBRARY: &str = "mono-2.0-bdwgc.dll";
+// names the runtime ships as in other Unity builds, tried in order
+pub const MONO_LIBRARIES: [&str; 4] = [
+    MONO_LIBRARY,
+    "mono-2.0-sgen.dll",
+    "mono-2.0-boehm.dll",
+    "mono.dll",
+];
+
+// MonoString, after the object header
+// length (int32)
+pub const STRING_LENGTH: usize = OBJECT_HEADER_SIZE;
+// chars (UTF-16)
+pub const STRING_CHARS: usize = OBJECT_HEADER_SIZE + 4;
 // offset in _MonoAssembly to field 'image' (Type MonoImage*)
 pub const ASSEMBLY_IMAGE: u32 = 0x10 + 0x50;
 // field 'domain_assemblies' in _MonoDomain (domain-internals.h)
@@ -13,11 +76,20 @@ pub const REFERENCED_ASSEMBLIES: u32 = 0xa0;
 pub const IMAGE_CLASS_CACHE: u32 = 0x4d0;
 pub const HASH_TABLE_SIZE: u32 = 0xc + 0xc;
 pub const HASH_TABLE_TABLE: u32 = 0x14 + 0xc;
+// fields 'raw_data' and 'raw_data_len' in _MonoImage, the assembly file as
+// loaded, metadata included
+pub const IMAGE_RAW_DATA: usize = 0x10;
+pub const IMAGE_RAW_DATA_LEN: usize = 0x18;
+// bigger images are not read to look up constants
+pub const MAX_IMAGE_SIZE: usize = 0x8000000;
 
 // _MonoClass
 // instance_size
 pub const TYPE_DEFINITION_FIELD_SIZE: u32 = 0x10 + 0x10;
 
+// instance_size, the object header included
+pub const TYPE_DEFINITION_INSTANCE_SIZE: u32 = 0x1c;
+
 // starting from size_inited, valuetype, enumtype
 pub const TYPE_DEFINITION_BIT_FIELDS: u32 = 0x14 + 0xc;
 
@@ -28,11 +100,15 @@ pub const TYPE_DEFINITION_CLASS_KIND: u32 = 0x1b; // alt: 0x1e + 0xc
 pub const TYPE_DEFINITION_PARENT: u32 = 0x30;
 // nested_in
 pub const TYPE_DEFINITION_NESTED_IN: u32 = 0x38;
+// image
+pub const TYPE_DEFINITION_IMAGE: u32 = 0x40;
 // name
 pub const TYPE_DEFINITION_NAME: u32 = 0x48;
 // name_space
 pub const TYPE_DEFINITION_NAMESPACE: u32 = 0x50; // 0x48 + 0x8
 
+// type_token (metadata token of the TypeDef row)
+pub const TYPE_DEFINITION_TYPE_TOKEN: u32 = 0x58;
 // vtable_size
 pub const TYPE_DEFINITION_V_TABLE_SIZE: u32 = 0x5C; // 0x50 + 0x8 + 0x4
 
@@ -54,9 +130,34 @@ pub const TYPE_DEFINITION_FIELD_COUNT: u32 = 0xa4 + 0x34 + 0x18 + 0x10; // 0xE0
 
 // next_class_cache
 pub const TYPE_DEFINITION_NEXT_CLASS_CACHE: u32 = 0xa8 + 0x34 + 0x18 + 0x10 + 0x4; // 0xE4
+
+// MonoClassGenericInst
+// generic_class, right after MonoClass (same place as MonoClassDef flags)
 pub const TYPE_DEFINITION_MONO_GENERIC_CLASS: u32 = 0x94 + 0x34 + 0x18 + 0x10;
 pub const TYPE_DEFINITION_GENERIC_CONTAINER: u32 = 0x110;
 
+// MonoType, after the data pointer: a 16 bit field attributes mask
+// followed by the 8 bit type code
+pub const TYPE_ATTRS: usize = SIZE_OF_PTR;
+pub const TYPE_CODE_SHIFT: u32 = 16;
+// FIELD_ATTRIBUTE_STATIC
+pub const FIELD_ATTRIBUTE_STATIC: u32 = 0x10;
+// FIELD_ATTRIBUTE_LITERAL, consts have no storage at all
+pub const FIELD_ATTRIBUTE_LITERAL: u32 = 0x40;
+
+// MonoGenericInst
+// type_argc (22 bit field after the 4 byte id)
+pub const GENERIC_INST_TYPE_ARGC: usize = 0x4;
+// type_argv
+pub const GENERIC_INST_TYPE_ARGV: usize = 0x8;
+
+// MonoGenericParam, data of a VAR/MVAR type
+// num (uint16) after the owner container
+pub const GENERIC_PARAM_NUM: usize = SIZE_OF_PTR;
+
+// sanity bound for the number of generic arguments of a type
+pub const MAX_GENERIC_ARGUMENTS: u32 = 32;
+
 pub const TYPE_DEFINITION_RUNTIME_INFO_DOMAIN_V_TABLES: u32 = 0x2 + 0x6; // 2 byte 'max_domain' + allignment to pointer size
 
 // MonoVTable.vtable
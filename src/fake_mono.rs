@@ -0,0 +1,283 @@
+// Mono structures laid out in our own memory the way the runtime does, so the
+// parsing can be tested without MTGA running. Read them back through
+// MonoReader::new(std::process::id()).
+use std::ffi::CString;
+
+use crate::constants;
+use crate::type_code::TypeCode;
+
+// MonoClass with its MonoClassField list, a vtable pointing back to it and
+// the static storage of the class
+pub struct FakeClass {
+    class: Box<[usize]>,
+    vtable: Box<[usize]>,
+    _runtime_info: Box<[usize]>,
+    static_data: Box<[usize]>,
+    _fields: Box<[usize]>,
+    types: Box<[[usize; 2]]>,
+    names: Vec<CString>,
+}
+
+impl FakeClass {
+    // fields are (name, type code, offset), instance offsets count the
+    // object header (vtable + sync) like mono does
+    pub fn new(name: &str, fields: &[(&str, u8, i32)]) -> Self {
+        Self::with_statics(name, fields, &[], &[])
+    }
+
+    // statics are (name, type code, offset) too, their offsets are inside
+    // static_data
+    pub fn with_statics(
+        name: &str,
+        fields: &[(&str, u8, i32)],
+        statics: &[(&str, u8, i32)],
+        static_data: &[usize],
+    ) -> Self {
+        let mut names = vec![CString::new(name).unwrap(), CString::new("").unwrap()];
+
+        let all_fields: Vec<(&str, u8, i32, u32)> = fields
+            .iter()
+            .map(|(name, code, offset)| (*name, *code, *offset, 0))
+            .chain(statics.iter().map(|(name, code, offset)| {
+                (*name, *code, *offset, constants::FIELD_ATTRIBUTE_STATIC)
+            }))
+            .collect();
+
+        // MonoType: data, attrs (type code in bits 16-23)
+        let types: Box<[[usize; 2]]> = all_fields
+            .iter()
+            .map(|(_, code, _, attrs)| {
+                [
+                    0,
+                    ((*code as u32) << constants::TYPE_CODE_SHIFT | attrs) as usize,
+                ]
+            })
+            .collect();
+
+        // MonoClassField: type, name, parent, offset
+        let mut field_list = Vec::new();
+        for (index, (field_name, _, offset, _)) in all_fields.iter().enumerate() {
+            let field_name = CString::new(*field_name).unwrap();
+            field_list.extend([
+                types[index].as_ptr() as usize,
+                field_name.as_ptr() as usize,
+                0,
+                *offset as usize,
+            ]);
+            names.push(field_name);
+        }
+        let field_list = field_list.into_boxed_slice();
+
+        let mut class = vec![0usize; 0x120 / constants::SIZE_OF_PTR].into_boxed_slice();
+        class[constants::TYPE_DEFINITION_NAME as usize / 8] = names[0].as_ptr() as usize;
+        class[constants::TYPE_DEFINITION_NAMESPACE as usize / 8] = names[1].as_ptr() as usize;
+        class[constants::TYPE_DEFINITION_FIELDS as usize / 8] = field_list.as_ptr() as usize;
+        class[constants::TYPE_DEFINITION_FIELD_COUNT as usize / 8] = all_fields.len();
+
+        // MonoVTable: klass first, the static storage pointer right after the
+        // (empty) method slots
+        let static_data: Box<[usize]> = static_data.into();
+        let mut vtable = vec![0usize; constants::V_TABLE as usize / 8 + 1].into_boxed_slice();
+        vtable[0] = class.as_ptr() as usize;
+        vtable[constants::V_TABLE as usize / 8] = static_data.as_ptr() as usize;
+
+        // MonoClassRuntimeInfo: max_domain, domain_vtables
+        let runtime_info: Box<[usize]> = Box::new([0, vtable.as_ptr() as usize]);
+        class[constants::TYPE_DEFINITION_RUNTIME_INFO as usize / 8] =
+            runtime_info.as_ptr() as usize;
+
+        FakeClass {
+            class,
+            vtable,
+            _runtime_info: runtime_info,
+            static_data,
+            _fields: field_list,
+            types,
+            names,
+        }
+    }
+
+    pub fn value_type(mut self) -> Self {
+        self.class[constants::TYPE_DEFINITION_BIT_FIELDS as usize / 8] |= 0x4;
+        self
+    }
+
+    // enums are value types too
+    pub fn enum_type(mut self) -> Self {
+        self.class[constants::TYPE_DEFINITION_BIT_FIELDS as usize / 8] |= 0x4 | 0x8;
+        self
+    }
+
+    // type code of the class's own MonoType (_byval_arg), like I4 for Int32,
+    // its data is the class
+    pub fn byval_type(mut self, code: u8) -> Self {
+        self.class[constants::TYPE_DEFINITION_BY_VAL_ARG as usize / 8] = self.address();
+        self.class[constants::TYPE_DEFINITION_BY_VAL_ARG as usize / 8 + 1] =
+            (code as usize) << constants::TYPE_CODE_SHIFT;
+        self
+    }
+
+    // _byval_arg of a generic instance, its data is the MonoGenericClass
+    // (container_class first)
+    pub fn generic_instance_of(mut self, generic_class: usize) -> Self {
+        self.class[constants::TYPE_DEFINITION_BY_VAL_ARG as usize / 8] = generic_class;
+        self.class[constants::TYPE_DEFINITION_BY_VAL_ARG as usize / 8 + 1] =
+            (TypeCode::GENERICINST as usize) << constants::TYPE_CODE_SHIFT;
+        self
+    }
+
+    // instance_size shares its word with idepth, rank and class_kind
+    pub fn instance_size(mut self, size: u32) -> Self {
+        self.class[constants::TYPE_DEFINITION_INSTANCE_SIZE as usize / 8] |= (size as usize) << 32;
+        self
+    }
+
+    // array class of the given element class
+    pub fn array_of(mut self, element: &FakeClass) -> Self {
+        self.class[0] = element.address();
+        self
+    }
+
+    // data of the MonoType of a field, the class of a VALUETYPE or the
+    // generic parameter of a VAR
+    pub fn field_type_data(mut self, index: usize, data: usize) -> Self {
+        self.types[index][0] = data;
+        self
+    }
+
+    // element size of an array class
+    pub fn element_size(mut self, size: usize) -> Self {
+        self.class[constants::TYPE_DEFINITION_SIZE as usize / 8] = size;
+        self
+    }
+
+    pub fn in_namespace(mut self, namespace: &str) -> Self {
+        let namespace = CString::new(namespace).unwrap();
+        self.class[constants::TYPE_DEFINITION_NAMESPACE as usize / 8] = namespace.as_ptr() as usize;
+        self.names.push(namespace);
+        self
+    }
+
+    pub fn nested_in(mut self, outer: &FakeClass) -> Self {
+        self.class[constants::TYPE_DEFINITION_NESTED_IN as usize / 8] = outer.address();
+        self
+    }
+
+    // MonoImage the class was loaded from and its TypeDef token, the token
+    // shares its word with the vtable size
+    pub fn in_image(mut self, image: usize, type_token: u32) -> Self {
+        self.class[constants::TYPE_DEFINITION_IMAGE as usize / 8] = image;
+        self.class[constants::TYPE_DEFINITION_TYPE_TOKEN as usize / 8] |= type_token as usize;
+        self
+    }
+
+    pub fn set_static(&mut self, index: usize, value: usize) {
+        self.static_data[index] = value;
+    }
+
+    pub fn address(&self) -> usize {
+        self.class.as_ptr() as usize
+    }
+
+    pub fn vtable(&self) -> usize {
+        self.vtable.as_ptr() as usize
+    }
+}
+
+// MonoArray: object header, bounds, max_length, then the elements
+pub fn fake_array(length: usize, elements: &[u8]) -> Box<[u8]> {
+    let mut array = vec![0u8; constants::ARRAY_MAX_LENGTH];
+    array.extend_from_slice(&length.to_le_bytes());
+    array.extend_from_slice(elements);
+    array.into_boxed_slice()
+}
+
+fn u16s(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+// ECMA-335 metadata root with the types Color (fields Red = -2 and Blue) and
+// Other (field Name = "hi") when their field lists are [1, 3]
+pub fn fake_metadata_root(field_lists: [u16; 2]) -> Vec<u8> {
+    // #~ with TypeDef (2 rows), Field (3 rows) and Constant (2 rows)
+    let mut tables = vec![0, 0, 0, 0, 2, 0, 0, 1];
+    tables.extend_from_slice(&(1u64 << 0x02 | 1 << 0x04 | 1 << 0x0b).to_le_bytes());
+    tables.extend_from_slice(&0u64.to_le_bytes());
+    for rows in [2u32, 3, 2] {
+        tables.extend_from_slice(&rows.to_le_bytes());
+    }
+    // TypeDef: flags, name, namespace, extends, field list, method list
+    for (name, field_list) in [(1, field_lists[0]), (7, field_lists[1])] {
+        tables.extend_from_slice(&0u32.to_le_bytes());
+        tables.extend(u16s(&[name, 0, 0, field_list, 1]));
+    }
+    // Field: flags, name, signature
+    tables.extend(u16s(&[0x56, 13, 0, 0x56, 17, 0, 0x56, 22, 0]));
+    // Constant: type, parent (field << 2), value
+    tables.extend(u16s(&[0x08, 1 << 2, 1, 0x0e, 3 << 2, 6]));
+
+    let strings = b"\0Color\0Other\0Red\0Blue\0Name\0".to_vec();
+    let blob = vec![0, 4, 0xfe, 0xff, 0xff, 0xff, 4, b'h', 0, b'i', 0];
+
+    let mut root = 0x424a5342u32.to_le_bytes().to_vec();
+    root.extend(u16s(&[1, 1, 0, 0]));
+    root.extend_from_slice(&4u32.to_le_bytes());
+    root.extend_from_slice(b"v4\0\0");
+    root.extend(u16s(&[0, 3]));
+
+    let headers_size = 12 + 20 + 16;
+    let mut offset = root.len() + headers_size;
+    for (name, stream) in [
+        (&b"#~\0\0"[..], &tables),
+        (&b"#Strings\0\0\0\0"[..], &strings),
+        (&b"#Blob\0\0\0"[..], &blob),
+    ] {
+        root.extend_from_slice(&(offset as u32).to_le_bytes());
+        root.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+        root.extend_from_slice(name);
+        offset += stream.len();
+    }
+    root.extend(tables);
+    root.extend(strings);
+    root.extend(blob);
+
+    root
+}
+
+// PE32+ file with one section mapping its RVAs to file offsets, the CLI
+// header pointing at the metadata root that follows it
+pub fn fake_pe_image(metadata_root: &[u8]) -> Vec<u8> {
+    const PE: usize = 0x40;
+    const CLI_HEADER: usize = 0x200;
+    const ROOT: usize = CLI_HEADER + 0x48;
+
+    let mut data = vec![0u8; ROOT];
+    data[0x3c..0x40].copy_from_slice(&(PE as u32).to_le_bytes());
+    data[PE..PE + 4].copy_from_slice(b"PE\0\0");
+    // one section, optional header of 112 bytes then 16 data directories
+    data[PE + 6..PE + 8].copy_from_slice(&1u16.to_le_bytes());
+    data[PE + 20..PE + 22].copy_from_slice(&(112u16 + 16 * 8).to_le_bytes());
+    let optional_header = PE + 24;
+    data[optional_header..optional_header + 2].copy_from_slice(&0x20bu16.to_le_bytes());
+    let cli_directory = optional_header + 112 + 14 * 8;
+    data[cli_directory..cli_directory + 4].copy_from_slice(&(CLI_HEADER as u32).to_le_bytes());
+
+    // section header: virtual size, virtual address 0, raw data at 0
+    let section = optional_header + 112 + 16 * 8;
+    let size = (ROOT + metadata_root.len()) as u32;
+    data[section + 8..section + 12].copy_from_slice(&size.to_le_bytes());
+
+    // CLI header: the metadata directory
+    data[CLI_HEADER + 8..CLI_HEADER + 12].copy_from_slice(&(ROOT as u32).to_le_bytes());
+
+    data.extend_from_slice(metadata_root);
+    data
+}
+
+// MonoImage with the raw data of its file, the data has to outlive it
+pub fn fake_image(raw_data: &[u8]) -> Box<[usize]> {
+    let mut image = vec![0usize; constants::IMAGE_RAW_DATA_LEN / 8 + 1].into_boxed_slice();
+    image[constants::IMAGE_RAW_DATA / 8] = raw_data.as_ptr() as usize;
+    image[constants::IMAGE_RAW_DATA_LEN / 8] = raw_data.len();
+    image
+}
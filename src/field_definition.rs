@@ -1,7 +1,7 @@
-use crate::{constants, MonoReader};
-use crate::type_code::TypeCode;
 use crate::type_info::TypeInfo;
+use crate::{constants, MonoReader};
 
+#[derive(Clone)]
 pub struct FieldDefinition {
     pub type_info: TypeInfo,
     pub name: String,
@@ -19,35 +19,7 @@ impl FieldDefinition {
         let offset = reader.read_i32(addr + constants::SIZE_OF_PTR * 3 as usize);
 
         // Get the generic type arguments
-        let mut generic_type_args = Vec::new();
-        let code = type_info.clone().code();
-        match code {
-            TypeCode::GENERICINST => {
-                let mono_generic_class_address = type_info.clone().data;
-                let mono_class_address = reader.read_ptr(mono_generic_class_address);
-                // this.Image.GetTypeDefinition(mono_class_address);
-
-                let mono_generic_container_ptr =
-                    mono_class_address + constants::TYPE_DEFINITION_GENERIC_CONTAINER as usize;
-                let mono_generic_container_address = reader.read_ptr(mono_generic_container_ptr);
-
-                let mono_generic_context_ptr = mono_generic_class_address + constants::SIZE_OF_PTR;
-                let mono_generic_ins_ptr = reader.read_ptr(mono_generic_context_ptr);
-
-                // var argument_count = this.Process.ReadInt32(mono_generic_ins_ptr + 0x4);
-                let argument_count =
-                    reader.read_u32(mono_generic_container_address + (4 * constants::SIZE_OF_PTR));
-                let type_arg_v_ptr = mono_generic_ins_ptr + 0x8;
-
-                for i in 0..argument_count {
-                    let generic_type_argument_ptr =
-                        reader.read_ptr(type_arg_v_ptr + (i as usize * constants::SIZE_OF_PTR));
-                    let t = TypeInfo::new(generic_type_argument_ptr, reader);
-                    generic_type_args.push(t);
-                }
-            }
-            _ => {}
-        }
+        let generic_type_args = type_info.generic_type_args(reader);
 
         FieldDefinition {
             type_info,
@@ -57,3 +29,28 @@ impl FieldDefinition {
         }
     }
 }
+
+// Name of the field the compiler generates to back an auto property
+pub fn backing_field_name(property_name: &str) -> String {
+    format!("<{}>k__BackingField", property_name)
+}
+
+// Field names can be given either as is or as the property they back
+pub fn matches_field_name(field_name: &str, name: &str) -> bool {
+    field_name == name || field_name == backing_field_name(name)
+}
+
+// Name of the property a backing field was generated for, None for any
+// other field
+pub fn property_name(field_name: &str) -> Option<&str> {
+    field_name
+        .strip_prefix('<')?
+        .strip_suffix(">k__BackingField")
+        .filter(|name| !name.is_empty())
+}
+
+// Names the compiler generates (backing fields, closures, iterator state)
+// start with '<', a character C# identifiers can't have
+pub fn is_compiler_generated(field_name: &str) -> bool {
+    field_name.starts_with('<')
+}
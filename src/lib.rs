@@ -1,37 +1,72 @@
+pub mod calibrate;
 pub mod constants;
+#[cfg(any(test, feature = "fake-mono"))]
+pub mod fake_mono;
 pub mod field_definition;
 pub mod managed;
+pub mod metadata;
 pub mod mono_class_kind;
 pub mod mono_reader;
 pub mod pe_reader;
+pub mod read_result;
+pub mod search;
 pub mod type_code;
 pub mod type_definition;
 pub mod type_info;
+pub mod unity_version;
 
+use field_definition::{is_compiler_generated, property_name, FieldDefinition};
 use managed::Managed;
 use mono_reader::MonoReader;
+use read_result::ReadResult;
 use type_code::TypeCode;
 use type_definition::TypeDefinition;
 use type_info::TypeInfo;
 
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use napi::bindgen_prelude::Buffer;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
 use napi_derive::napi;
 
+// Class tables at least this big are scanned by several threads
+const PARALLEL_SCAN_THRESHOLD: usize = 2000;
+const SCAN_THREADS: usize = 4;
+
 // Utility fn to get the reader and initialize it
-pub fn get_reader(process_name: String) -> Option<MonoReader> {
+pub fn get_reader(process_name: String) -> Result<MonoReader, String> {
     let pid = MonoReader::find_pid_by_name(&process_name);
 
     if pid.is_none() {
-        return None;
+        return Err("Process not found".to_string());
     }
 
     let pid = pid.iter().next().unwrap();
 
-    let mut mono_reader = MonoReader::new(pid.as_u32());
-    mono_reader.read_mono_root_domain();
-    mono_reader.read_assembly_image();
-    return Some(mono_reader);
+    return get_reader_by_pid(pid.as_u32());
+}
+
+// Same as get_reader for an explicit pid, to target one of several running
+// instances of the game
+pub fn get_reader_by_pid(pid: u32) -> Result<MonoReader, String> {
+    if !MonoReader::pid_exists(pid) {
+        return Err("Process not found".to_string());
+    }
+
+    // without elevation every read fails and comes back as a zero, say so
+    // instead of returning empty data
+    if !MonoReader::is_admin() {
+        return Err("Administrator/root privileges required to read the game memory".to_string());
+    }
+
+    let mut mono_reader = MonoReader::new(pid);
+    mono_reader.select_mono_runtime()?;
+    mono_reader.validate()?;
+
+    return Ok(mono_reader);
 }
 
 pub fn get_def_by_name<'a>(
@@ -39,222 +74,3588 @@ pub fn get_def_by_name<'a>(
     name: String,
     mono_reader: &MonoReader,
 ) -> Option<&'a usize> {
+    let index = if defs.len() < PARALLEL_SCAN_THRESHOLD {
+        defs.iter()
+            .position(|def| class_name_matches(mono_reader, *def, &name))
+    } else {
+        find_def_parallel(defs, &name, mono_reader.pid())
+    };
+
+    index.map(|index| &defs[index])
+}
+
+// Same as get_def_by_name, also matching the namespace of the class when
+// there is one ("" for the global namespace). Nested classes are named from
+// their outermost class, as "Outer/Inner" or "Outer+Inner". Their own
+// namespace is empty, the one of the outermost class is matched instead.
+pub fn get_def_by_full_name<'a>(
+    defs: &'a [usize],
+    name: &str,
+    namespace: Option<&str>,
+    mono_reader: &MonoReader,
+) -> Option<&'a usize> {
+    let mut names: Vec<&str> = name.split(NESTED_CLASS_SEPARATORS).collect();
+    let class_name = names.pop().unwrap_or_default();
+
     defs.iter().find(|def| {
-        let main_typedef = TypeDefinition::new(**def, &mono_reader);
-        main_typedef.name == name
+        if !class_name_matches(mono_reader, **def, class_name) {
+            return false;
+        }
+
+        let mut outermost = **def;
+        for outer_name in names.iter().rev() {
+            outermost =
+                mono_reader.read_ptr(outermost + constants::TYPE_DEFINITION_NESTED_IN as usize);
+            if outermost == 0 || !class_name_matches(mono_reader, outermost, outer_name) {
+                return false;
+            }
+        }
+
+        // only a top-level class has a namespace, a nested one would pass
+        // for a global type with its empty one
+        namespace.is_none_or(|namespace| {
+            mono_reader.read_ptr(outermost + constants::TYPE_DEFINITION_NESTED_IN as usize) == 0
+                && mono_reader.read_ptr_ascii_string(
+                    outermost + constants::TYPE_DEFINITION_NAMESPACE as usize,
+                ) == namespace.trim()
+        })
     })
 }
 
-#[napi]
-pub fn read_data(process_name: String, fields: Vec<String>) -> serde_json::Value {
-    println!("Reading started...");
-
-    let reader = get_reader(process_name);
+// Separate the declaring classes in the name of a nested class, / like
+// reflection's FullName in IL and + like Type.FullName
+const NESTED_CLASS_SEPARATORS: [char; 2] = ['/', '+'];
 
-    match reader {
-        None => return json!({ "error": "Process not found" }),
-        Some(mut mono_reader) => {
-            let defs = mono_reader.create_type_definitions();
+// Only reads the name of the class, building a whole TypeDefinition for
+// every class of the image takes dozens of reads each
+fn class_name_matches(mono_reader: &MonoReader, definition: usize, name: &str) -> bool {
+    mono_reader.read_ptr_ascii_string(definition + constants::TYPE_DEFINITION_NAME as usize) == name
+}
 
-            // get the type defs on the root of the assembly for the first loop
-            let definition = get_def_by_name(&defs, fields[0].clone(), &mono_reader)
-                .unwrap()
-                .clone();
+// Splits the scan of the class table across threads, each with its own
+// reader, keeping the first match in table order like the sequential scan
+fn find_def_parallel(defs: &[usize], name: &str, pid: u32) -> Option<usize> {
+    let first_match = AtomicUsize::new(usize::MAX);
+    let chunk_size = defs.len().div_ceil(SCAN_THREADS).max(1);
 
-            // skipt the first item in the find array
-            let find = &fields[1..];
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in defs.chunks(chunk_size).enumerate() {
+            let first_match = &first_match;
+            scope.spawn(move || {
+                let reader = MonoReader::new(pid);
+                let start = chunk_index * chunk_size;
 
-            let mut field = (definition.clone(), TypeInfo::new(definition, &mono_reader));
+                for (offset, def) in chunk.iter().enumerate() {
+                    // another thread already matched an earlier class
+                    if start + offset > first_match.load(Ordering::Relaxed) {
+                        return;
+                    }
 
-            for (index, name) in find.iter().enumerate() {
-                field = match index {
-                    0 => {
-                        let class = TypeDefinition::new(definition, &mono_reader);
-                        class.get_static_value(name)
+                    if class_name_matches(&reader, *def, name) {
+                        first_match.fetch_min(start + offset, Ordering::Relaxed);
+                        return;
                     }
-                    _ => {
-                        let managed = Managed::new(&mono_reader, field.0, None);
-                        let ptr = mono_reader.read_ptr(field.0);
-                        let code = field.1.clone().code();
-                        let class = match code {
-                            TypeCode::GENERICINST => managed.read_generic_instance(field.1.clone()),
-                            _ => managed.read_class(),
-                        };
-                        class.get_value(name, ptr)
+                }
+            });
+        }
+    });
+
+    match first_match.into_inner() {
+        usize::MAX => None,
+        index => Some(index),
+    }
+}
+
+// Walks the fields path starting from the static fields of the root class,
+// returning the location and type of the last field. Every step is pushed to
+// the trace so a broken link in the path can be spotted.
+fn resolve_path(
+    mono_reader: &MonoReader,
+    defs: &Vec<usize>,
+    fields: &[String],
+    trace: &mut Vec<serde_json::Value>,
+) -> Result<(usize, TypeInfo), String> {
+    if fields.is_empty() {
+        return Err("No fields to read".to_string());
+    }
+
+    // get the type defs on the root of the assembly for the first loop
+    let definition = match get_def_by_name(defs, fields[0].clone(), mono_reader) {
+        Some(def) => *def,
+        None => {
+            trace.push(json!({
+                "field": fields[0],
+                "address": 0,
+                "type_code": "CLASS",
+                "ok": false
+            }));
+            return Err(format!("Class not found: {}", fields[0]));
+        }
+    };
+
+    trace.push(json!({
+        "field": fields[0],
+        "address": definition,
+        "type_code": "CLASS",
+        "ok": true
+    }));
+
+    // skipt the first item in the find array
+    let find = &fields[1..];
+
+    let mut field = (definition, TypeInfo::new(definition, mono_reader));
+
+    for (index, name) in find.iter().enumerate() {
+        field = match index {
+            0 => {
+                let class = TypeDefinition::new(definition, mono_reader);
+                match class.get_static_value(name) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        trace.push(json!({
+                            "field": name,
+                            "address": 0,
+                            "type_code": TypeCode::END.to_string(),
+                            "ok": false
+                        }));
+                        return Err(format!("{}: {}.{}", e, fields[0], name));
                     }
-                };
-                let code = field.1.clone();
-                println!("Find: {}: {} {}", name, code.code(), field.0);
+                }
             }
+            _ => {
+                // the object's vtable gives its concrete class, for generic
+                // instances that's the inflated class and not the definition
+                let managed = Managed::new(mono_reader, field.0, None);
+                let ptr = mono_reader.read_ptr(field.0);
+                let class = managed.read_class();
+                class.get_value(name, ptr)
+            }
+        };
+        let code = field.1.clone();
+        println!("Find: {}: {} {}", name, code.clone().code(), field.0);
 
-            let managed = Managed::new(&mono_reader, field.0, None);
-            let ptr = mono_reader.read_ptr(field.0);
-            let code = field.1.clone().code();
+        let ok = field.0 != 0;
+        trace.push(json!({
+            "field": name,
+            "address": field.0,
+            "type_code": code.code().to_string(),
+            "ok": ok
+        }));
 
-            let strout = match code {
-                TypeCode::CLASS => {
-                    let mut class = managed.read_class();
-                    class.set_fields_base(ptr);
-                    class.to_string()
-                }
-                TypeCode::GENERICINST => {
-                    let mut class = managed.read_generic_instance(field.1.clone());
-                    class.set_fields_base(ptr);
-                    class.to_string()
-                }
-                TypeCode::SZARRAY => managed.read_managed_array(),
-                _ => {
-                    println!("Code: {} strout not implemented", code);
-                    String::from("{}")
-                }
+        if !ok {
+            return Err(format!("Field {} resolved to a null address", name));
+        }
+    }
+
+    Ok(field)
+}
+
+// Reads the value at the location returned by resolve_path
+fn read_field_output(mono_reader: &MonoReader, field: (usize, TypeInfo)) -> String {
+    let managed = Managed::new(mono_reader, field.0, None);
+    let code = field.1.clone().code();
+
+    match code {
+        TypeCode::CLASS | TypeCode::GENERICINST | TypeCode::OBJECT => {
+            managed.read_instance_field(&field.1, 0)
+        }
+        TypeCode::SZARRAY => managed.read_managed_array(),
+        // primitives are stored inline at the resolved location
+        TypeCode::BOOLEAN
+        | TypeCode::U4
+        | TypeCode::U
+        | TypeCode::R4
+        | TypeCode::R8
+        | TypeCode::I4
+        | TypeCode::I
+        | TypeCode::I2
+        | TypeCode::U2
+        | TypeCode::I8
+        | TypeCode::U8
+        | TypeCode::STRING
+        | TypeCode::VALUETYPE => managed.read_value(field.1),
+        _ => {
+            println!("Code: {} strout not implemented", code);
+            String::from("{}")
+        }
+    }
+}
+
+// Parses the JSON produced by the readers, dropping control characters
+fn to_json(strout: String) -> serde_json::Value {
+    let clean_str = strout
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    let json = serde_json::from_str(&clean_str);
+    return match json {
+        Ok(j) => j,
+        Err(e) => {
+            println!("Error: {}", e);
+            json!({ "error": e.to_string() })
+        }
+    };
+}
+
+// In strict mode a read that fell back to 0 on the way fails the whole
+// result, instead of leaving a 0 or null in it that looks like a real value
+fn check_failed_reads(
+    mono_reader: &MonoReader,
+    failed_before: u64,
+    strict: Option<bool>,
+    value: serde_json::Value,
+) -> serde_json::Value {
+    let failed = mono_reader.failed_reads() - failed_before;
+    if strict.unwrap_or(false) && failed > 0 {
+        return json!({ "error": format!("{} reads failed", failed) });
+    }
+
+    value
+}
+
+#[napi]
+pub fn read_data(
+    process_name: String,
+    fields: Vec<String>,
+    strict: Option<bool>,
+) -> serde_json::Value {
+    println!("Reading started...");
+
+    return match get_reader(process_name) {
+        Ok(mut mono_reader) => read_data_with_reader(&mut mono_reader, &fields, strict),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+// Same as read_data, attaching to the process with the given pid instead of
+// looking it up by name
+#[napi]
+pub fn read_data_by_pid(pid: u32, fields: Vec<String>, strict: Option<bool>) -> serde_json::Value {
+    return match get_reader_by_pid(pid) {
+        Ok(mut mono_reader) => read_data_with_reader(&mut mono_reader, &fields, strict),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+fn read_data_with_reader(
+    mono_reader: &mut MonoReader,
+    fields: &[String],
+    strict: Option<bool>,
+) -> serde_json::Value {
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let failed_before = mono_reader.failed_reads();
+    let mut trace = Vec::new();
+    let value = match resolve_path(mono_reader, &defs, fields, &mut trace) {
+        Ok(field) => to_json(read_field_output(mono_reader, field)),
+        Err(e) => return json!({ "error": e }),
+    };
+
+    check_failed_reads(mono_reader, failed_before, strict, value)
+}
+
+// Same as read_data with a typed result for Rust users, dictionaries are
+// returned as their (key, value) pairs, with the values that are
+// dictionaries as nested { key, value } arrays
+pub fn read_data_typed(process_name: String, fields: Vec<String>) -> ReadResult {
+    let mut mono_reader = match get_reader(process_name) {
+        Ok(reader) => reader,
+        Err(e) => return ReadResult::Error(e),
+    };
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return ReadResult::Error(e),
+    };
+
+    let mut trace = Vec::new();
+    let field = match resolve_path(&mono_reader, &defs, &fields, &mut trace) {
+        Ok(field) => field,
+        Err(e) => return ReadResult::Error(e),
+    };
+
+    if let TypeCode::CLASS | TypeCode::GENERICINST = field.1.clone().code() {
+        let mut budget = constants::MAX_NESTED_DICTIONARY_ENTRIES;
+        if let Some(pairs) = Managed::new(&mono_reader, field.0, None)
+            .read_dict_nested(constants::MAX_NESTED_DICTIONARY_DEPTH, &mut budget)
+        {
+            return ReadResult::Dictionary(pairs);
+        }
+    }
+
+    return ReadResult::from(to_json(read_field_output(&mono_reader, field)));
+}
+
+// Same as read_data, but also returns every step of the path with its
+// resolved address and type, to debug paths broken by game updates
+#[napi]
+pub fn read_data_traced(process_name: String, fields: Vec<String>) -> serde_json::Value {
+    let reader = get_reader(process_name);
+
+    match reader {
+        Err(e) => return json!({ "error": e }),
+        Ok(mut mono_reader) => {
+            let defs = match mono_reader.create_type_definitions() {
+                Ok(defs) => defs,
+                Err(e) => return json!({ "error": e }),
             };
 
-            let return_string = strout.clone();
-
-            let clean_str = return_string
-                .chars()
-                .filter(|c| !c.is_control())
-                .collect::<String>();
-            let json = serde_json::from_str(&clean_str);
-            return match json {
-                Ok(j) => j,
-                Err(e) => {
-                    println!("Error: {}", e);
-                    serde_json::from_str(&format!("{{ \"error\": \"{}\" }}", e)).unwrap()
-                }
+            let mut trace = Vec::new();
+            let result = match resolve_path(&mono_reader, &defs, &fields, &mut trace) {
+                Ok(field) => to_json(read_field_output(&mono_reader, field)),
+                Err(e) => json!({ "error": e }),
             };
+
+            return json!({ "result": result, "trace": trace });
         }
     }
 }
 
+// Resolves the path from its static root and returns the current location of
+// the last field, to use with read_class/read_fields/read_list. Objects can be
+// moved by the GC between polls, so call this again on every poll instead of
+// keeping the address around.
+#[napi]
+pub fn resolve_from_root(process_name: String, fields: Vec<String>) -> serde_json::Value {
+    return match get_reader(process_name) {
+        Ok(mut mono_reader) => resolve_from_root_with_reader(&mut mono_reader, &fields),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+fn resolve_from_root_with_reader(
+    mono_reader: &mut MonoReader,
+    fields: &[String],
+) -> serde_json::Value {
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let mut trace = Vec::new();
+    return match resolve_path(mono_reader, &defs, fields, &mut trace) {
+        Ok((address, type_info)) => json!({
+            "address": address,
+            "type_code": type_info.code().to_string()
+        }),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+// Entries of each collection field read by read_class_expanded
+const DEFAULT_INLINE_ENTRIES: u32 = 20;
+
+// max_fields and skip_pointers bound the output for big classes, to get
+// only the first fields or only the ones stored inline
+#[napi]
+pub fn read_class(
+    process_name: String,
+    address: i64,
+    max_fields: Option<u32>,
+    skip_pointers: Option<bool>,
+    strict: Option<bool>,
+) -> serde_json::Value {
+    return match get_reader(process_name) {
+        Ok(mono_reader) => read_class_with_reader(
+            &mono_reader,
+            address,
+            max_fields,
+            skip_pointers,
+            None,
+            strict,
+        ),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+// Same as read_class with the List and Dictionary fields read inline, their
+// first max_entries entries (20 by default) next to their count, to see an
+// object with its small collections in one call
+#[napi]
+pub fn read_class_expanded(
+    process_name: String,
+    address: i64,
+    max_entries: Option<u32>,
+    strict: Option<bool>,
+) -> serde_json::Value {
+    return match get_reader(process_name) {
+        Ok(mono_reader) => read_class_with_reader(
+            &mono_reader,
+            address,
+            None,
+            None,
+            Some(max_entries.unwrap_or(DEFAULT_INLINE_ENTRIES)),
+            strict,
+        ),
+        Err(e) => json!({ "error": e }),
+    };
+}
+
+fn read_class_with_reader(
+    mono_reader: &MonoReader,
+    address: i64,
+    max_fields: Option<u32>,
+    skip_pointers: Option<bool>,
+    max_entries: Option<u32>,
+    strict: Option<bool>,
+) -> serde_json::Value {
+    let failed_before = mono_reader.failed_reads();
+    let managed = Managed::new(mono_reader, address as usize, None);
+    let ptr = mono_reader.read_ptr(address as usize);
+
+    let mut class = managed.read_class();
+    class.set_fields_base(ptr);
+    class.set_max_fields(max_fields);
+    class.set_skip_pointers(skip_pointers.unwrap_or(false));
+    class.set_max_collection_entries(max_entries);
+    let value = to_json(class.to_string());
+
+    check_failed_reads(mono_reader, failed_before, strict, value)
+}
+
 #[napi]
-pub fn read_class(process_name: String, address: i64) -> serde_json::Value {
+pub fn read_generic_instance(process_name: String, address: i64) -> serde_json::Value {
     let reader = get_reader(process_name);
 
     match reader {
-        None => return json!({ "error": "Process not found" }),
-        Some(mono_reader) => {
+        Err(e) => return json!({ "error": e }),
+        Ok(mono_reader) => {
             let managed = Managed::new(&mono_reader, address as usize, None);
             let ptr = mono_reader.read_ptr(address as usize);
 
-            let mut class = managed.read_class();
+            let mut class = managed.read_generic_instance(TypeInfo::new(ptr, &mono_reader));
             class.set_fields_base(ptr);
-            let return_string = class.to_string();
-
-            let clean_str = return_string
-                .chars()
-                .filter(|c| !c.is_control())
-                .collect::<String>();
-            let json = serde_json::from_str(&clean_str);
-            return match json {
-                Ok(j) => j,
-                Err(e) => {
-                    println!("Error: {}", e);
-                    serde_json::from_str(&format!("{{ \"error\": \"{}\" }}", e)).unwrap()
-                }
-            };
+            return to_json(class.to_string());
         }
     }
 }
 
+// Reads a field of the object pointed by address, the property name can be
+// given without the compiler generated "<Name>k__BackingField" mangling
 #[napi]
-pub fn read_generic_instance(process_name: String, address: i64) -> serde_json::Value {
+pub fn read_property(
+    process_name: String,
+    address: i64,
+    property_name: String,
+) -> serde_json::Value {
     let reader = get_reader(process_name);
 
     match reader {
-        None => return json!({ "error": "Process not found" }),
-        Some(mono_reader) => {
+        Err(e) => return json!({ "error": e }),
+        Ok(mono_reader) => {
             let managed = Managed::new(&mono_reader, address as usize, None);
             let ptr = mono_reader.read_ptr(address as usize);
 
-            let mut class = managed.read_generic_instance(TypeInfo::new(ptr, &mono_reader));
-            class.set_fields_base(ptr);
-            let return_string = class.to_string();
-
-            let clean_str = return_string
-                .chars()
-                .filter(|c| !c.is_control())
-                .collect::<String>();
-            let json = serde_json::from_str(&clean_str);
-            return match json {
-                Ok(j) => j,
-                Err(e) => {
-                    println!("Error: {}", e);
-                    serde_json::from_str(&format!("{{ \"error\": \"{}\" }}", e)).unwrap()
-                }
+            let class = managed.read_class();
+            if class.get_field(&property_name).0 == 0 {
+                return json!({ "error": format!("Property not found: {}", property_name) });
+            }
+
+            let field = class.get_value(&property_name, ptr);
+            return to_json(read_field_output(&mono_reader, field));
+        }
+    }
+}
+
+// Returns the raw memory at address, to inspect unknown layouts
+#[napi]
+pub fn read_raw(process_name: String, address: i64, length: i32) -> napi::Result<Buffer> {
+    if address <= 0 {
+        return Err(napi::Error::from_reason("Invalid address"));
+    }
+    if length <= 0 || length as usize > constants::MAX_RAW_READ_LENGTH {
+        return Err(napi::Error::from_reason(format!(
+            "Length must be between 1 and {}",
+            constants::MAX_RAW_READ_LENGTH
+        )));
+    }
+
+    let reader = get_reader(process_name);
+
+    match reader {
+        Err(e) => return Err(napi::Error::from_reason(e)),
+        Ok(mono_reader) => {
+            return match mono_reader.read_bytes(address as usize, length as usize) {
+                Some(bytes) => Ok(bytes.into()),
+                None => Err(napi::Error::from_reason(format!(
+                    "Could not read {} bytes at {:x}",
+                    length, address
+                ))),
             };
         }
     }
 }
 
+// Reads several fields of the object pointed by address at once, resolving
+// its class a single time
 #[napi]
-pub fn find_pid_by_name(process_name: String) -> bool {
-    let results = MonoReader::find_pid_by_name(&process_name);
+pub fn read_fields(
+    process_name: String,
+    address: i64,
+    field_names: Vec<String>,
+) -> serde_json::Value {
+    return match get_reader(process_name) {
+        Ok(mono_reader) => read_fields_with_reader(&mono_reader, address, field_names),
+        Err(e) => json!({ "error": e }),
+    };
+}
 
-    return match results {
-        Some(_pid) => true,
-        None => false,
+fn read_fields_with_reader(
+    mono_reader: &MonoReader,
+    address: i64,
+    field_names: Vec<String>,
+) -> serde_json::Value {
+    let managed = Managed::new(mono_reader, address as usize, None);
+    let ptr = mono_reader.read_ptr(address as usize);
+
+    let class = managed.read_class();
+    let values = class.get_values(&field_names, ptr);
+
+    let mut result = serde_json::Map::new();
+    for (name, value) in field_names.into_iter().zip(values) {
+        let json = match value {
+            Some(field) => to_json(read_field_output(mono_reader, field)),
+            None => json!({ "error": format!("Field not found: {}", name) }),
+        };
+        result.insert(name, json);
+    }
+
+    return serde_json::Value::Object(result);
+}
+
+// Reads the List<T> pointed by address, class elements are expanded into
+// their fields up to depth levels (1 by default, 0 returns addresses)
+#[napi]
+pub fn read_list(process_name: String, address: i64, depth: Option<u32>) -> serde_json::Value {
+    return match get_reader(process_name) {
+        Ok(mono_reader) => read_list_with_reader(&mono_reader, address, depth),
+        Err(e) => json!({ "error": e }),
     };
 }
 
+fn read_list_with_reader(
+    mono_reader: &MonoReader,
+    address: i64,
+    depth: Option<u32>,
+) -> serde_json::Value {
+    let managed = Managed::new(mono_reader, address as usize, None);
+    return to_json(managed.read_managed_list(depth.unwrap_or(1)));
+}
+
+// Reads the cards of a deck, address holds its List of card entries (the
+// grpId and count structs, stored inline in the list). Only the first
+// _size entries of the list are in use.
 #[napi]
-pub fn is_admin() -> bool {
-    let results = MonoReader::is_admin();
-    return results;
+pub fn read_deck(process_name: String, address: i64) -> serde_json::Value {
+    match get_reader(process_name) {
+        Ok(mono_reader) => read_deck_with_reader(&mono_reader, address),
+        Err(e) => json!({ "error": e }),
+    }
 }
 
-#[test]
-fn test_find_no_process() {
-    let process_name = "_____test";
+fn read_deck_with_reader(mono_reader: &MonoReader, address: i64) -> serde_json::Value {
+    let cards = read_list_with_reader(mono_reader, address, Some(1));
+    if !cards.is_array() {
+        return json!({ "error": "Deck card list could not be read" });
+    }
 
-    let results = MonoReader::find_pid_by_name(&process_name);
+    cards
+}
 
-    assert_eq!(results.is_none(), true);
+// Reads the Dictionary pointed by address with the given key and value type
+// names (as reported by get_class_details, like "I4" or "STRING"), for when
+// the types of its class can't be used. When the types are unknown or can't
+// be read inline, the entries are returned undecoded as
+// { raw_entries: [{ hashCode, next, raw }] } with raw the rest of the entry
+// in hex. A key type that isn't a type name, like "auto", is taken from the
+// dictionary's comparer. Reference values are returned as their address, or
+// expanded up to depth levels (nested dictionaries as their entries, objects
+// as their fields).
+#[napi]
+pub fn read_dictionary_typed(
+    process_name: String,
+    address: i64,
+    key_type: String,
+    value_type: String,
+    depth: Option<u32>,
+    strict: Option<bool>,
+) -> serde_json::Value {
+    let reader = get_reader(process_name);
+
+    match reader {
+        Err(e) => return json!({ "error": e }),
+        Ok(mono_reader) => {
+            let failed_before = mono_reader.failed_reads();
+            let managed = Managed::new(&mono_reader, address as usize, None);
+
+            let key_type = type_info_by_name(&key_type).or_else(|| managed.read_dict_key_type());
+            let pairs = match (key_type, type_info_by_name(&value_type)) {
+                (Some(key), Some(value)) => managed.read_dict_typed_expanded(
+                    &key,
+                    &value,
+                    depth
+                        .unwrap_or(0)
+                        .min(constants::MAX_NESTED_DICTIONARY_DEPTH),
+                ),
+                _ => None,
+            };
+            let pairs = pairs.or_else(|| managed.read_concurrent_dict_pairs());
+
+            // the raw entries are only worth reading when the types didn't fit
+            let value = match pairs {
+                Some(pairs) => json!(pairs
+                    .into_iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect::<Vec<_>>()),
+                None => match managed.read_dict_raw() {
+                    Some(entries) => json!({ "raw_entries": entries }),
+                    None => return json!({ "error": "Not a dictionary" }),
+                },
+            };
+
+            return check_failed_reads(&mono_reader, failed_before, strict, value);
+        }
+    }
 }
 
-#[test]
-fn test_find_mtga() {
-    let process_name = "MTGA";
+// Reads the Dictionary pointed by address on a worker thread, calling back
+// with { entries: [{ key, value }], done: false } every few hundred entries
+// and { entries: [], done: true } at the end, so a big collection doesn't
+// have to be built and converted in one go. Errors are passed as the
+// callback's first argument.
+#[napi]
+pub fn read_dictionary_stream(
+    process_name: String,
+    address: i64,
+    callback: JsFunction,
+) -> napi::Result<()> {
+    let tsfn: ThreadsafeFunction<serde_json::Value, ErrorStrategy::CalleeHandled> =
+        callback.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
 
-    let results = MonoReader::find_pid_by_name(&process_name);
+    std::thread::spawn(move || {
+        let mono_reader = match get_reader(process_name) {
+            Ok(mono_reader) => mono_reader,
+            Err(e) => {
+                tsfn.call(
+                    Err(napi::Error::from_reason(e)),
+                    ThreadsafeFunctionCallMode::Blocking,
+                );
+                return;
+            }
+        };
 
-    assert_eq!(results.is_some(), true);
+        let managed = Managed::new(&mono_reader, address as usize, None);
+        let read = managed.read_dict_batches(None, constants::DICTIONARY_STREAM_BATCH, |batch| {
+            let entries = batch
+                .into_iter()
+                .map(|(key, value)| json!({ "key": key, "value": value }))
+                .collect::<Vec<_>>();
+            tsfn.call(
+                Ok(json!({ "entries": entries, "done": false })),
+                ThreadsafeFunctionCallMode::Blocking,
+            );
+        });
+
+        let end = match read {
+            Some(()) => Ok(json!({ "entries": [], "done": true })),
+            None => Err(napi::Error::from_reason("Not a dictionary")),
+        };
+        tsfn.call(end, ThreadsafeFunctionCallMode::Blocking);
+    });
+
+    return Ok(());
 }
 
-#[test]
-fn test_read_cards() {
-    let path = vec![
-        "WrapperController".to_string(),
-        "<Instance>k__BackingField".to_string(),
-        "<InventoryManager>k__BackingField".to_string(),
-        "_inventoryServiceWrapper".to_string(),
-        "<Cards>k__BackingField".to_string(),
-        "_entries".to_string(),
-    ];
+// A bare TypeInfo for the type code with the given name, either as
+// displayed ("INT (I4)") or just its code ("I4")
+fn type_info_by_name(name: &str) -> Option<TypeInfo> {
+    (0..=0xff).find_map(|type_code| {
+        let type_info = TypeInfo::from_code(type_code);
 
-    let data = read_data("MTGA".to_string(), path);
-    assert_eq!(data.is_array(), true);
+        let type_name = type_info.clone().code().to_string();
+        if type_name == name || type_name.ends_with(&format!("({})", name)) {
+            Some(type_info)
+        } else {
+            None
+        }
+    })
+}
 
-    let any_entry = data.get(0).unwrap();
-    assert_eq!(any_entry.is_object(), true);
-    println!("{:?}", any_entry);
-    assert_eq!(any_entry.get("key").unwrap().is_number(), true);
-    assert_eq!(any_entry.get("value").unwrap().is_number(), true);
+// Lists every loaded assembly with its image address and number of classes,
+// or class_count_error when its class cache can't be read
+#[napi]
+pub fn get_assemblies_detailed(process_name: String) -> serde_json::Value {
+    let reader = get_reader(process_name);
+
+    match reader {
+        Err(e) => return json!({ "error": e }),
+        Ok(mono_reader) => {
+            let assemblies = mono_reader
+                .get_assemblies()
+                .into_iter()
+                .map(|(name, image_address)| {
+                    match mono_reader.create_type_definitions_for_image(image_address) {
+                        Ok(defs) => json!({
+                            "name": name,
+                            "image_address": image_address,
+                            "class_count": defs.len()
+                        }),
+                        Err(e) => json!({
+                            "name": name,
+                            "image_address": image_address,
+                            "class_count_error": e
+                        }),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            return json!(assemblies);
+        }
+    }
 }
 
-#[test]
-fn test_read_formats() {
-    let path = vec![
-        "PAPA".to_string(),
-        "_instance".to_string(),
-        "_formatManager".to_string(),
-        "_formats".to_string(),
-        "_items".to_string(),
-    ];
+// Type definitions of the loaded assembly with that name
+fn assembly_type_definitions(
+    mono_reader: &MonoReader,
+    assembly_name: &str,
+) -> Result<Vec<usize>, String> {
+    let image_address = mono_reader
+        .get_assemblies()
+        .into_iter()
+        .find(|(name, _)| name == assembly_name)
+        .map(|(_, image_address)| image_address)
+        .ok_or_else(|| format!("Assembly not found: {}", assembly_name))?;
 
-    let data = read_data("MTGA".to_string(), path);
-    println!("{}", data.to_string());
-    assert_eq!(data.is_object(), true);
+    mono_reader.create_type_definitions_for_image(image_address)
+}
+
+// Lists the classes of an assembly a page at a time, offset and limit pick
+// the page after the namespace (exact) and name (substring) filters. Only
+// the names are read, so browsing an assembly with thousands of types
+// doesn't build a TypeDefinition for each.
+#[napi]
+pub fn get_assembly_classes(
+    process_name: String,
+    assembly_name: String,
+    offset: Option<u32>,
+    limit: Option<u32>,
+    namespace: Option<String>,
+    name_filter: Option<String>,
+) -> serde_json::Value {
+    let mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let defs = match assembly_type_definitions(&mono_reader, &assembly_name) {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let read_name =
+        |def: usize, offset: u32| mono_reader.read_ptr_ascii_string(def + offset as usize);
+
+    let matching: Vec<usize> = if namespace.is_none() && name_filter.is_none() {
+        defs
+    } else {
+        defs.into_iter()
+            .filter(|def| {
+                namespace.as_ref().is_none_or(|namespace| {
+                    read_name(*def, constants::TYPE_DEFINITION_NAMESPACE) == *namespace
+                }) && name_filter.as_ref().is_none_or(|filter| {
+                    read_name(*def, constants::TYPE_DEFINITION_NAME).contains(filter.as_str())
+                })
+            })
+            .collect()
+    };
+
+    let classes = matching
+        .iter()
+        .skip(offset.unwrap_or(0) as usize)
+        .take(limit.map_or(usize::MAX, |limit| limit as usize))
+        .map(|def| {
+            json!({
+                "name": read_name(*def, constants::TYPE_DEFINITION_NAME),
+                "namespace": read_name(*def, constants::TYPE_DEFINITION_NAMESPACE),
+                "address": def
+            })
+        })
+        .collect::<Vec<_>>();
+
+    return json!({ "total": matching.len(), "classes": classes });
+}
+
+// Lists the live objects held by the static "instance" fields of every
+// class of an assembly, what get_class_details shows for one class, to find
+// the entry points of a new build
+#[napi]
+pub fn find_singletons(process_name: String, assembly_name: String) -> serde_json::Value {
+    let mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    match assembly_type_definitions(&mono_reader, &assembly_name) {
+        Ok(defs) => json!(find_singletons_in(&mono_reader, &defs)),
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+fn find_singletons_in(mono_reader: &MonoReader, defs: &[usize]) -> Vec<serde_json::Value> {
+    let mut singletons = Vec::new();
+
+    for def in defs {
+        let class = TypeDefinition::new(*def, mono_reader);
+        // statics are only allocated once the class is initialized
+        if class.v_table == 0 {
+            continue;
+        }
+
+        for (field_name, address) in class.get_static_instances() {
+            if let Some(object_class) = live_object_class(mono_reader, address) {
+                singletons.push(json!({
+                    "class": class.full_name(),
+                    "field_name": field_name,
+                    "address": address,
+                    "type": object_class.full_name()
+                }));
+            }
+        }
+    }
+
+    singletons
+}
+
+// Class of the object at ptr, if its vtable is the one its class points
+// back to, which a stale or wrong pointer almost never gets right
+fn live_object_class(mono_reader: &MonoReader, ptr: usize) -> Option<TypeDefinition<'_>> {
+    let vtable = mono_reader
+        .maybe_read_ptr(ptr)
+        .filter(|vtable| *vtable != 0)?;
+    let class = mono_reader
+        .maybe_read_ptr(vtable)
+        .filter(|class| *class != 0)?;
+
+    let definition = TypeDefinition::new(class, mono_reader);
+    if definition.v_table != vtable || !mono_reader::is_plausible_class_name(&definition.name) {
+        return None;
+    }
+
+    Some(definition)
+}
+
+// Levels of references followed by search_fields by default
+const DEFAULT_SEARCH_DEPTH: u32 = 4;
+
+// Dotted paths of the fields whose name contains name (ignoring case),
+// among the objects reachable from the one at address within max_depth
+// references. Paths start at the object, not at address.
+#[napi]
+pub fn search_fields(
+    process_name: String,
+    address: i64,
+    name: String,
+    max_depth: Option<u32>,
+) -> serde_json::Value {
+    match get_reader(process_name) {
+        Ok(mono_reader) => json!(search_fields_in(
+            &mono_reader,
+            address as usize,
+            &name,
+            max_depth.unwrap_or(DEFAULT_SEARCH_DEPTH)
+        )),
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+fn search_fields_in(
+    mono_reader: &MonoReader,
+    location: usize,
+    name: &str,
+    max_depth: u32,
+) -> Vec<String> {
+    let name = name.to_lowercase();
+    search::search_graph(mono_reader, location, max_depth, |field_def, _| {
+        field_def.name.to_lowercase().contains(&name)
+    })
+}
+
+// Describes a class of Assembly-CSharp: its fields and the live instances
+// held by its static "instance" fields
+// namespace picks the class when several namespaces define one with that
+// name, without it the first class with the name is used. Nested classes
+// are named as Outer/Inner or Outer+Inner.
+#[napi]
+pub fn get_class_details(
+    process_name: String,
+    class_name: String,
+    namespace: Option<String>,
+) -> serde_json::Value {
+    let reader = get_reader(process_name);
+
+    match reader {
+        Err(e) => return json!({ "error": e }),
+        Ok(mut mono_reader) => {
+            let defs = match mono_reader.create_type_definitions() {
+                Ok(defs) => defs,
+                Err(e) => return json!({ "error": e }),
+            };
+
+            let definition = if namespace.is_some() || class_name.contains(NESTED_CLASS_SEPARATORS)
+            {
+                get_def_by_full_name(&defs, &class_name, namespace.as_deref(), &mono_reader)
+            } else {
+                get_def_by_name(&defs, class_name.clone(), &mono_reader)
+            };
+            let definition = match definition {
+                Some(def) => *def,
+                None => return json!({ "error": format!("Class not found: {}", class_name) }),
+            };
+
+            let class = TypeDefinition::new(definition, &mono_reader);
+
+            let field_defs = class
+                .get_fields()
+                .into_iter()
+                .map(|field| FieldDefinition::new(field, &mono_reader))
+                .collect::<Vec<_>>();
+
+            // the metadata is only read when there are constants to show
+            let constant_values = if field_defs.iter().any(|def| def.type_info.is_const) {
+                class.get_constant_values()
+            } else {
+                Vec::new()
+            };
+
+            let fields = field_defs
+                .into_iter()
+                .map(|field_def| {
+                    let mut field = json!({
+                        "name": field_def.name,
+                        "type": field_def.type_info.clone().code().to_string(),
+                        "type_code": field_def.type_info.type_code,
+                        "is_reference": field_def.type_info.is_reference(&mono_reader),
+                        "offset": field_def.offset,
+                        "is_static": field_def.type_info.is_static,
+                        "is_const": field_def.type_info.is_const,
+                        "is_backing_field": property_name(&field_def.name).is_some(),
+                        "is_compiler_generated": is_compiler_generated(&field_def.name)
+                    });
+                    if let Some(property) = property_name(&field_def.name) {
+                        field["property_name"] = json!(property);
+                    }
+                    if let Some((_, value)) = constant_values
+                        .iter()
+                        .find(|(name, _)| *name == field_def.name)
+                    {
+                        field["constant_value"] = value.clone();
+                    }
+                    field
+                })
+                .collect::<Vec<_>>();
+
+            let static_instances = class
+                .get_static_instances()
+                .into_iter()
+                .map(
+                    |(field_name, address)| json!({ "field_name": field_name, "address": address }),
+                )
+                .collect::<Vec<_>>();
+
+            let nested_types = class
+                .get_nested_types(&defs)
+                .into_iter()
+                .map(|nested| {
+                    let nested_class = TypeDefinition::new(nested, &mono_reader);
+                    json!({
+                        "name": nested_class.name,
+                        "full_name": nested_class.full_name(),
+                        "address": nested
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            return json!({
+                "name": class.name,
+                "namespace": class.namespace_name,
+                "full_name": class.full_name(),
+                "address": definition,
+                "is_enum": class.is_enum,
+                "is_value_type": class.is_value_type,
+                "fields": fields,
+                "static_instances": static_instances,
+                "nested_types": nested_types
+            });
+        }
+    }
+}
+
+// The MonoType of a field of the class at class_address as read, to check
+// the type code still comes from the bits of attrs the reader takes it from
+// after a game update
+#[napi]
+pub fn get_field_type_info(
+    process_name: String,
+    class_address: i64,
+    field_name: String,
+) -> serde_json::Value {
+    match get_reader(process_name) {
+        Ok(mono_reader) => field_type_info(&mono_reader, class_address as usize, &field_name),
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+fn field_type_info(mono_reader: &MonoReader, class: usize, field_name: &str) -> serde_json::Value {
+    let (field, type_info) = TypeDefinition::new(class, mono_reader).get_field(field_name);
+    if field == 0 {
+        return json!({ "error": format!("Field not found: {}", field_name) });
+    }
+
+    json!({
+        "attrs": type_info.attrs,
+        "type_code": type_info.type_code,
+        "type": type_info.clone().code().to_string(),
+        "data": type_info.data,
+        "is_static": type_info.is_static,
+        "is_const": type_info.is_const
+    })
+}
+
+// Path to the player's card collection, a Dictionary<uint, int> of grpId to count
+const CARDS_PATH: [&str; 5] = [
+    "WrapperController",
+    "<Instance>k__BackingField",
+    "<InventoryManager>k__BackingField",
+    "_inventoryServiceWrapper",
+    "<Cards>k__BackingField",
+];
+
+// (grpId, count) of every card of the player's collection
+fn read_collection(mono_reader: &mut MonoReader) -> Result<Vec<(u32, i32)>, String> {
+    let defs = mono_reader.create_type_definitions()?;
+
+    let fields: Vec<String> = CARDS_PATH.iter().map(|f| f.to_string()).collect();
+    let (location, _) = resolve_path(mono_reader, &defs, &fields, &mut Vec::new())?;
+
+    Managed::new(mono_reader, location, None)
+        .read_dict_entries(None)
+        .ok_or_else(|| String::from("Card collection could not be read"))
+}
+
+// The collection as trackers import it, { "<grpId>": count }
+fn collection_to_json(entries: Vec<(u32, i32)>) -> serde_json::Value {
+    let collection: BTreeMap<u32, i32> = entries.into_iter().collect();
+
+    let mut result = serde_json::Map::new();
+    for (grp_id, count) in collection {
+        result.insert(grp_id.to_string(), json!(count));
+    }
+
+    serde_json::Value::Object(result)
+}
+
+// Reads the whole card collection, every entry of the Cards dictionary
+#[napi]
+pub fn export_collection(process_name: String) -> serde_json::Value {
+    let mut mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    match read_collection(&mut mono_reader) {
+        Ok(entries) => collection_to_json(entries),
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+// (grpId, delta) for every card added, removed or whose count changed
+fn diff_card_counts(old: &BTreeMap<i64, i64>, new: &BTreeMap<i64, i64>) -> Vec<(i64, i64)> {
+    let mut changes = Vec::new();
+
+    for (grp_id, count) in new {
+        let delta = count - old.get(grp_id).unwrap_or(&0);
+        if delta != 0 {
+            changes.push((*grp_id, delta));
+        }
+    }
+
+    for (grp_id, count) in old {
+        if !new.contains_key(grp_id) && *count != 0 {
+            changes.push((*grp_id, -count));
+        }
+    }
+
+    changes.sort();
+    changes
+}
+
+// Path to the AccountInformation of the logged in player (the one try.js
+// reads), null before login
+const ACCOUNT_PATH: [&str; 4] = [
+    "PAPA",
+    "_instance",
+    "_accountClient",
+    "<AccountInformation>k__BackingField",
+];
+
+// (AccountInformation field, key in the result of read_account)
+const ACCOUNT_FIELDS: [(&str, &str); 2] =
+    [("DisplayName", "screen_name"), ("AccountID", "user_id")];
+
+// Reads the screen name and id of the logged in account as
+// { screen_name, user_id }. A field missing from AccountInformation makes
+// the whole call an error instead of an object with an error in it.
+#[napi]
+pub fn read_account(process_name: String) -> serde_json::Value {
+    let mut mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let path: Vec<String> = ACCOUNT_PATH.iter().map(|name| name.to_string()).collect();
+    match resolve_path(&mono_reader, &defs, &path, &mut Vec::new()) {
+        Ok((location, _)) => read_account_at(&mono_reader, location),
+        Err(e) => json!({ "error": e }),
+    }
+}
+
+fn read_account_at(mono_reader: &MonoReader, location: usize) -> serde_json::Value {
+    let not_logged_in = json!({ "error": "Not logged in" });
+    if mono_reader.maybe_read_ptr(location).unwrap_or(0) == 0 {
+        return not_logged_in;
+    }
+
+    let field_names = ACCOUNT_FIELDS
+        .iter()
+        .map(|(field, _)| field.to_string())
+        .collect();
+    let values = read_fields_with_reader(mono_reader, location as i64, field_names);
+
+    let mut account = serde_json::Map::new();
+    for (field, key) in ACCOUNT_FIELDS {
+        if values[field].get("error").is_some() {
+            return values[field].clone();
+        }
+        account.insert(key.to_string(), values[field].clone());
+    }
+
+    // the account object can exist before the login fills it in
+    if account["screen_name"].is_null() && account["user_id"].is_null() {
+        return not_logged_in;
+    }
+
+    serde_json::Value::Object(account)
+}
+
+// A reader attached once to a process, for several reads in a row or to
+// read more than one game from the same Node process. The functions taking
+// a process name attach again on every call.
+#[napi]
+pub struct ReaderHandle {
+    reader: MonoReader,
+}
+
+#[napi]
+pub fn open(process_name: String) -> napi::Result<ReaderHandle> {
+    return match get_reader(process_name) {
+        Ok(reader) => Ok(ReaderHandle { reader }),
+        Err(e) => Err(napi::Error::from_reason(e)),
+    };
+}
+
+#[napi]
+pub fn open_by_pid(pid: u32) -> napi::Result<ReaderHandle> {
+    return match get_reader_by_pid(pid) {
+        Ok(reader) => Ok(ReaderHandle { reader }),
+        Err(e) => Err(napi::Error::from_reason(e)),
+    };
+}
+
+#[napi]
+impl ReaderHandle {
+    #[napi]
+    pub fn pid(&self) -> u32 {
+        self.reader.pid()
+    }
+
+    #[napi]
+    pub fn read_data(&mut self, fields: Vec<String>, strict: Option<bool>) -> serde_json::Value {
+        read_data_with_reader(&mut self.reader, &fields, strict)
+    }
+
+    #[napi]
+    pub fn resolve_from_root(&mut self, fields: Vec<String>) -> serde_json::Value {
+        resolve_from_root_with_reader(&mut self.reader, &fields)
+    }
+
+    #[napi]
+    pub fn read_class(
+        &self,
+        address: i64,
+        max_fields: Option<u32>,
+        skip_pointers: Option<bool>,
+        strict: Option<bool>,
+    ) -> serde_json::Value {
+        read_class_with_reader(
+            &self.reader,
+            address,
+            max_fields,
+            skip_pointers,
+            None,
+            strict,
+        )
+    }
+
+    #[napi]
+    pub fn read_fields(&self, address: i64, field_names: Vec<String>) -> serde_json::Value {
+        read_fields_with_reader(&self.reader, address, field_names)
+    }
+
+    #[napi]
+    pub fn read_list(&self, address: i64, depth: Option<u32>) -> serde_json::Value {
+        read_list_with_reader(&self.reader, address, depth)
+    }
+}
+
+// Keeps the last seen card collection so trackers can poll for what changed
+// ("gained 2x cardX") instead of diffing the whole collection themselves.
+// The first poll reports every owned card as added.
+#[napi]
+pub struct CardCollectionWatcher {
+    process_name: String,
+    last_seen: BTreeMap<i64, i64>,
+}
+
+#[napi]
+impl CardCollectionWatcher {
+    #[napi(constructor)]
+    pub fn new(process_name: String) -> Self {
+        CardCollectionWatcher {
+            process_name,
+            last_seen: BTreeMap::new(),
+        }
+    }
+
+    // Returns [{ grpId, delta }] since the previous poll, on error the last
+    // seen collection is kept so the next poll diffs against it
+    #[napi]
+    pub fn poll(&mut self) -> serde_json::Value {
+        let mut mono_reader = match get_reader(self.process_name.clone()) {
+            Ok(reader) => reader,
+            Err(e) => return json!({ "error": e }),
+        };
+        let entries = match read_collection(&mut mono_reader) {
+            Ok(entries) => entries,
+            Err(e) => return json!({ "error": e }),
+        };
+
+        let counts: BTreeMap<i64, i64> = entries
+            .into_iter()
+            .map(|(grp_id, count)| (grp_id as i64, count as i64))
+            .collect();
+
+        let changes: Vec<serde_json::Value> = diff_card_counts(&self.last_seen, &counts)
+            .into_iter()
+            .map(|(grp_id, delta)| json!({ "grpId": grp_id, "delta": delta }))
+            .collect();
+
+        self.last_seen = counts;
+
+        return json!(changes);
+    }
+}
+
+// The Unity version of the game next to the offsets the reader assumes, to
+// check a reader matches the game build when diagnosing wrong reads
+#[napi]
+pub fn get_runtime_info(process_name: String) -> serde_json::Value {
+    let unity_version = MonoReader::find_pid_by_name(&process_name)
+        .and_then(|pid| MonoReader::exe_path(pid.as_u32()))
+        .and_then(|exe_path| unity_version::detect_unity_version(&exe_path))
+        .map(|version| version.to_string());
+
+    return json!({
+        "unity_version": unity_version,
+        "offset_profile": constants::OFFSETS_PROFILE,
+        "runtime": constants::RUNTIME,
+        "ptr_size": constants::SIZE_OF_PTR
+    });
+}
+
+// Searches the MonoClass offsets of the field list and count after a Unity
+// update, class_name is a class whose expected_fields (some of its field
+// names, in declaration order) are known
+#[napi]
+pub fn calibrate_field_offsets(
+    process_name: String,
+    class_name: String,
+    expected_fields: Vec<String>,
+) -> serde_json::Value {
+    let mut mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+    let definition = match get_def_by_name(&defs, class_name.clone(), &mono_reader) {
+        Some(def) => *def,
+        None => return json!({ "error": format!("Class not found: {}", class_name) }),
+    };
+
+    match calibrate::find_field_offsets(&mono_reader, definition, &expected_fields) {
+        Some(offsets) => json!({
+            "fields": offsets.fields,
+            "field_count": offsets.field_count,
+            "alternatives": offsets.alternatives,
+            "profile": offsets.profile()
+        }),
+        None => json!({
+            "error": format!("No offsets give the expected fields of {}", class_name)
+        }),
+    }
+}
+
+// Compares the number of fields read for a class with the number its
+// metadata declares, a mismatch means the field offsets are off for this
+// Unity build (see calibrate_field_offsets)
+#[napi]
+pub fn check_field_count(process_name: String, class_name: String) -> serde_json::Value {
+    let mut mono_reader = match get_reader(process_name) {
+        Ok(mono_reader) => mono_reader,
+        Err(e) => return json!({ "error": e }),
+    };
+
+    let defs = match mono_reader.create_type_definitions() {
+        Ok(defs) => defs,
+        Err(e) => return json!({ "error": e }),
+    };
+    let definition = match get_def_by_name(&defs, class_name.clone(), &mono_reader) {
+        Some(def) => *def,
+        None => return json!({ "error": format!("Class not found: {}", class_name) }),
+    };
+
+    match TypeDefinition::new(definition, &mono_reader).check_field_count() {
+        Some((runtime_fields, metadata_fields)) => json!({
+            "runtime_fields": runtime_fields,
+            "metadata_fields": metadata_fields,
+            "matches": runtime_fields == metadata_fields as usize
+        }),
+        None => json!({ "error": format!("No metadata found for {}", class_name) }),
+    }
+}
+
+#[napi]
+pub fn find_pid_by_name(process_name: String) -> bool {
+    let results = MonoReader::find_pid_by_name(&process_name);
+
+    return match results {
+        Some(_pid) => true,
+        None => false,
+    };
+}
+
+// Total reads and bytes read so far, time a few read_data calls against it
+// to get the reads/sec a path costs
+#[napi]
+pub fn get_read_stats() -> serde_json::Value {
+    let stats = MonoReader::stats();
+
+    return json!({ "reads": stats.reads, "bytes": stats.bytes });
+}
+
+// Whether the game is far enough in its startup to be read, poll it after
+// launching the game instead of taking empty reads for a failure. Always
+// false without administrator/root privileges.
+#[napi]
+pub fn is_ready(process_name: String) -> bool {
+    let pid = match MonoReader::find_pid_by_name(&process_name) {
+        Some(pid) if MonoReader::is_admin() => pid.as_u32(),
+        _ => return false,
+    };
+
+    let mut mono_reader = MonoReader::new(pid);
+    if mono_reader.select_mono_runtime().is_err() {
+        return false;
+    }
+
+    mono_reader.is_ready()
+}
+
+#[napi]
+pub fn is_admin() -> bool {
+    let results = MonoReader::is_admin();
+    return results;
+}
+
+#[test]
+fn test_find_no_process() {
+    let process_name = "_____test";
+
+    let results = MonoReader::find_pid_by_name(&process_name);
+
+    assert_eq!(results.is_none(), true);
+}
+
+#[test]
+fn test_find_running_process() {
+    // at least the test binary itself, running with readable memory
+    let pid = MonoReader::find_pid_by_name("mtga_reader").unwrap();
+
+    let mut sys = sysinfo::System::new();
+    sys.refresh_process(pid);
+    let status = sys.process(pid).unwrap().status();
+    assert!(!matches!(
+        status,
+        sysinfo::ProcessStatus::Zombie | sysinfo::ProcessStatus::Dead
+    ));
+}
+
+#[test]
+fn test_pick_running_process() {
+    use mono_reader::pick_running_process;
+    use sysinfo::{Pid, ProcessStatus};
+
+    let (zombie, small, big, unreadable) = (
+        Pid::from_u32(1),
+        Pid::from_u32(2),
+        Pid::from_u32(3),
+        Pid::from_u32(4),
+    );
+
+    assert_eq!(
+        pick_running_process(&[
+            (zombie, ProcessStatus::Zombie, 4096, Some(true)),
+            (small, ProcessStatus::Run, 1024, Some(true)),
+        ]),
+        Some(small)
+    );
+
+    // the game over its launcher, and over a bigger one that can't be read
+    assert_eq!(
+        pick_running_process(&[
+            (small, ProcessStatus::Sleep, 1024, Some(true)),
+            (big, ProcessStatus::Run, 8192, Some(true)),
+            (unreadable, ProcessStatus::Run, 16384, Some(false)),
+        ]),
+        Some(big)
+    );
+
+    // unchecked memory counts as readable when there is some
+    assert_eq!(
+        pick_running_process(&[
+            (small, ProcessStatus::Run, 0, None),
+            (big, ProcessStatus::Run, 8192, None),
+        ]),
+        Some(big)
+    );
+
+    assert_eq!(
+        pick_running_process(&[
+            (small, ProcessStatus::Run, 1024, Some(false)),
+            (big, ProcessStatus::Dead, 8192, Some(false)),
+            (unreadable, ProcessStatus::Run, 0, None),
+        ]),
+        None
+    );
+}
+
+#[test]
+fn test_read_after_close() {
+    let value: u32 = 0xdeadbeef;
+    let addr = &value as *const u32 as usize;
+
+    let mut mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(mono_reader.maybe_read_u32(addr), Some(0xdeadbeef));
+
+    mono_reader.close();
+    assert_eq!(mono_reader.maybe_read_u32(addr), None);
+}
+
+#[test]
+fn test_is_plausible_class_name() {
+    use mono_reader::is_plausible_class_name;
+
+    assert!(is_plausible_class_name("PAPA"));
+    assert!(is_plausible_class_name("Dictionary`2"));
+    assert!(is_plausible_class_name("<>c__DisplayClass12_0"));
+    assert!(!is_plausible_class_name(""));
+    assert!(!is_plausible_class_name("\u{1}\u{7f}x"));
+    assert!(!is_plausible_class_name("PA PA"));
+}
+
+#[test]
+fn test_read_stats() {
+    let value: u32 = 0xdeadbeef;
+    let addr = &value as *const u32 as usize;
+    let mono_reader = MonoReader::new(std::process::id());
+
+    let before = MonoReader::stats();
+    mono_reader.read_u32(addr);
+    mono_reader.read_bytes(addr, 4);
+    let after = MonoReader::stats();
+
+    // other tests may be reading at the same time
+    assert!(after.reads >= before.reads + 2);
+    assert!(after.bytes >= before.bytes + 8);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_modules_in_maps() {
+    // a Proton process with the game runtime and the one of an injected tool
+    let maps = "\
+140000000-140001000 r--p 00000000 00:1f 1234 /games/MTGA/MTGA.exe
+6ffff0000000-6ffff0001000 r--p 00000000 00:1f 2345 /games/MTGA/MonoBleedingEdge/EmbedRuntime/mono-2.0-bdwgc.dll
+6ffff0001000-6ffff0200000 r-xp 00001000 00:1f 2345 /games/MTGA/MonoBleedingEdge/EmbedRuntime/mono-2.0-bdwgc.dll
+7ffff0000000-7ffff0001000 r--p 00000000 00:1f 3456 /home/user/My Tools/Mono.dll
+7ffff1000000-7ffff1021000 rw-p 00000000 00:00 0
+";
+
+    assert_eq!(
+        mono_reader::modules_in_maps(maps),
+        vec![
+            ("/games/MTGA/MTGA.exe".to_string(), 0x140000000),
+            (
+                "/games/MTGA/MonoBleedingEdge/EmbedRuntime/mono-2.0-bdwgc.dll".to_string(),
+                0x6ffff0000000
+            ),
+            ("/home/user/My Tools/Mono.dll".to_string(), 0x7ffff0000000),
+        ]
+    );
+}
+
+#[test]
+fn test_read_bytes_into() {
+    let value: u64 = 0x0807060504030201;
+    let addr = &value as *const u64 as usize;
+    let mono_reader = MonoReader::new(std::process::id());
+
+    let mut buf = [0u8; 8];
+    assert_eq!(mono_reader.read_bytes_into(addr, &mut buf), 8);
+    assert_eq!(buf, [1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(mono_reader.read_bytes_into(0x10, &mut buf), 0);
+
+    // longer than the chunks strings are read in
+    let name = std::ffi::CString::new("a".repeat(100)).unwrap();
+    assert_eq!(
+        mono_reader.read_ascii_string(name.as_ptr() as usize),
+        "a".repeat(100)
+    );
+}
+
+#[test]
+fn test_mono_reader_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MonoReader>();
+}
+
+#[test]
+fn test_unreadable_class_cache() {
+    let mut mono_reader = MonoReader::new(std::process::id());
+    assert!(!mono_reader.is_ready());
+
+    let error = mono_reader.create_type_definitions().unwrap_err();
+    assert!(error.contains("read_assembly_image"));
+
+    let mut image = vec![0usize; 0x600 / constants::SIZE_OF_PTR];
+    let error = mono_reader
+        .create_type_definitions_for_image(image.as_ptr() as usize)
+        .unwrap_err();
+    assert!(error.contains("image_class_cache offset"));
+
+    // one empty bucket
+    let table = [0usize];
+    let size = (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_SIZE) as usize;
+    let table_ptr = (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_TABLE) as usize;
+    image[size / 8] = 1;
+    image[table_ptr / 8] = table.as_ptr() as usize;
+
+    assert_eq!(
+        mono_reader.create_type_definitions_for_image(image.as_ptr() as usize),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn test_reader_handle_reads() {
+    let class = fake_mono::FakeClass::new("Card", &[("grpId", TypeCode::I4 as u8, 0x10)]);
+    let object: [usize; 3] = [class.vtable(), 0, 42];
+    let location = [object.as_ptr() as usize];
+
+    let handle = ReaderHandle {
+        reader: MonoReader::new(std::process::id()),
+    };
+
+    assert_eq!(handle.pid(), std::process::id());
+    assert_eq!(
+        handle.read_fields(location.as_ptr() as i64, vec!["grpId".to_string()]),
+        json!({ "grpId": 42 })
+    );
+}
+
+#[test]
+fn test_strict_reads() {
+    let class = fake_mono::FakeClass::new("Card", &[("grpId", TypeCode::I4 as u8, 0x10)]);
+    let object: [usize; 3] = [class.vtable(), 0, 42];
+    let location = [object.as_ptr() as usize];
+
+    // the vtable pointer of this one goes nowhere
+    let broken: [usize; 3] = [0x10, 0, 42];
+    let broken_location = [broken.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let read = |location: &[usize; 1], strict| {
+        read_class_with_reader(
+            &mono_reader,
+            location.as_ptr() as i64,
+            None,
+            None,
+            None,
+            strict,
+        )
+    };
+
+    assert_eq!(read(&location, Some(true)), json!({ "grpId": 42 }));
+    assert_eq!(mono_reader.failed_reads(), 0);
+
+    assert!(read(&broken_location, None).get("error").is_none());
+    assert!(mono_reader.failed_reads() > 0);
+
+    let error = read(&broken_location, Some(true));
+    assert!(error["error"].as_str().unwrap().ends_with("reads failed"));
+
+    // a reference field that can't be read is a null too, counted as failed
+    let failed_before = mono_reader.failed_reads();
+    let field = Managed::new(&mono_reader, 0x10, None);
+    let object_type = TypeInfo::from_code(TypeCode::OBJECT as u32);
+    assert_eq!(field.read_object(), "null");
+    assert_eq!(field.read_instance_field(&object_type, 0), "null");
+    assert_eq!(mono_reader.failed_reads(), failed_before + 2);
+
+    let error = check_failed_reads(&mono_reader, failed_before, Some(true), json!(null));
+    assert!(error["error"].as_str().unwrap().ends_with("reads failed"));
+}
+
+#[test]
+fn test_find_mtga() {
+    let process_name = "MTGA";
+
+    let results = MonoReader::find_pid_by_name(&process_name);
+
+    assert_eq!(results.is_some(), true);
+}
+
+#[test]
+fn test_read_cards() {
+    let path = vec![
+        "WrapperController".to_string(),
+        "<Instance>k__BackingField".to_string(),
+        "<InventoryManager>k__BackingField".to_string(),
+        "_inventoryServiceWrapper".to_string(),
+        "<Cards>k__BackingField".to_string(),
+        "_entries".to_string(),
+    ];
+
+    let data = read_data("MTGA".to_string(), path, None);
+    assert_eq!(data.is_array(), true);
+
+    let any_entry = data.get(0).unwrap();
+    assert_eq!(any_entry.is_object(), true);
+    println!("{:?}", any_entry);
+    assert_eq!(any_entry.get("key").unwrap().is_number(), true);
+    assert_eq!(any_entry.get("value").unwrap().is_number(), true);
+}
+
+#[test]
+fn test_collection_to_json() {
+    let entries = vec![(70123, 4), (512, 1), (68000, 2)];
+
+    assert_eq!(
+        collection_to_json(entries),
+        json!({ "512": 1, "68000": 2, "70123": 4 })
+    );
+    assert_eq!(collection_to_json(Vec::new()), json!({}));
+}
+
+#[test]
+fn test_diff_card_counts() {
+    let old = BTreeMap::from([(10, 4), (12, 1)]);
+    let new = BTreeMap::from([(10, 2), (13, 3)]);
+
+    assert_eq!(
+        diff_card_counts(&old, &new),
+        vec![(10, -2), (12, -1), (13, 3)]
+    );
+    assert_eq!(diff_card_counts(&new, &new), vec![]);
+}
+
+#[test]
+fn test_read_cards_by_property_names() {
+    let path = vec![
+        "WrapperController".to_string(),
+        "Instance".to_string(),
+        "InventoryManager".to_string(),
+        "_inventoryServiceWrapper".to_string(),
+        "Cards".to_string(),
+        "_entries".to_string(),
+    ];
+
+    let data = read_data("MTGA".to_string(), path, None);
+    assert_eq!(data.is_array(), true);
+}
+
+#[test]
+fn test_read_formats() {
+    let path = vec![
+        "PAPA".to_string(),
+        "_instance".to_string(),
+        "_formatManager".to_string(),
+        "_formats".to_string(),
+        "_items".to_string(),
+    ];
+
+    let data = read_data("MTGA".to_string(), path, None);
+    println!("{}", data.to_string());
+    assert_eq!(data.is_object(), true);
+}
+
+#[test]
+fn test_read_static_values() {
+    let papa = fake_mono::FakeClass::new("PAPA", &[]);
+    let instance: [usize; 2] = [papa.vtable(), 0];
+
+    // static int, long, ulong and PAPA _instance, each at its own offset of
+    // the static storage
+    let class = fake_mono::FakeClass::with_statics(
+        "Statics",
+        &[],
+        &[
+            ("count", TypeCode::I4 as u8, 0),
+            ("total", TypeCode::I8 as u8, 8),
+            ("mask", TypeCode::U8 as u8, 16),
+            ("_instance", TypeCode::CLASS as u8, 24),
+        ],
+        &[
+            (-7i32) as u32 as usize,
+            (-9_007_199_254_740_993i64) as usize,
+            u64::MAX as usize,
+            instance.as_ptr() as usize,
+        ],
+    );
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let definition = TypeDefinition::new(class.address(), &mono_reader);
+
+    // primitive statics are stored inline at the returned location
+    let read_static = |name: &str| {
+        let (location, type_info) = definition.get_static_value(name).unwrap();
+        Managed::new(&mono_reader, location, None).read_value(type_info)
+    };
+    assert_eq!(read_static("count"), "-7");
+    assert_eq!(read_static("total"), "\"-9007199254740993\"");
+    assert_eq!(read_static("mask"), "\"18446744073709551615\"");
+
+    // reference statics hold a pointer to the object at the returned location
+    let (location, _) = definition.get_static_value("_instance").unwrap();
+    assert_eq!(mono_reader.read_ptr(location), instance.as_ptr() as usize);
+    let managed = Managed::new(&mono_reader, location, None);
+    assert_eq!(managed.read_class().name, "PAPA");
+}
+
+#[test]
+fn test_value_type_field_offsets_match() {
+    // struct TestStruct { int a; int b; }, field offsets include the object
+    // header
+    let i4 = TypeCode::I4 as u8;
+    let class =
+        fake_mono::FakeClass::new("TestStruct", &[("a", i4, 0x10), ("b", i4, 0x14)]).value_type();
+
+    let instance: [i32; 2] = [1, 42];
+    let instance_addr = instance.as_ptr() as usize;
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    assert_eq!(class.is_value_type, true);
+
+    class.set_fields_base(instance_addr);
+    assert_eq!(class.to_string(), "{ \"a\": 1, \"b\": 42 }");
+
+    let (b_addr, _) = class.get_value("b", instance_addr);
+    assert_eq!(mono_reader.read_i32(b_addr), 42);
+}
+
+#[test]
+fn test_field_lookup_is_cached() {
+    let i4 = TypeCode::I4 as u8;
+    let class = fake_mono::FakeClass::new(
+        "Counter",
+        &[("_count", i4, 0x10), ("<Total>k__BackingField", i4, 0x14)],
+    );
+    let object: [i32; 6] = [0, 0, 0, 0, 3, 12];
+    let object_addr = object.as_ptr() as usize;
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let definition = TypeDefinition::new(class.address(), &mono_reader);
+    assert!(mono_reader
+        .cached_field(class.address(), "Counter", "Total")
+        .is_none());
+
+    let (total, _) = definition.get_value("Total", object_addr);
+    assert_eq!(mono_reader.read_i32(total), 12);
+    assert!(mono_reader
+        .cached_field(class.address(), "Counter", "Total")
+        .is_some());
+
+    // the batch lookup uses the cached field and fills in the other one
+    let values = definition.get_values(&["Total".to_string(), "_count".to_string()], object_addr);
+    let values: Vec<i32> = values
+        .into_iter()
+        .map(|value| mono_reader.read_i32(value.unwrap().0))
+        .collect();
+    assert_eq!(values, vec![12, 3]);
+    assert!(mono_reader
+        .cached_field(class.address(), "Counter", "_count")
+        .is_some());
+    assert!(mono_reader
+        .cached_field(class.address(), "Counter", "missing")
+        .is_none());
+}
+
+#[test]
+fn test_stale_field_cache_is_rescanned() {
+    let i4 = TypeCode::I4 as u8;
+    let old = fake_mono::FakeClass::new("Old", &[("_count", i4, 0x18)]);
+    let class = fake_mono::FakeClass::new("Counter", &[("_count", i4, 0x10)]);
+    let object: [i32; 6] = [0, 0, 0, 0, 3, 12];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let old_definition = TypeDefinition::new(old.address(), &mono_reader);
+    let (old_field, _) = old_definition.get_field("_count");
+    let old_def = FieldDefinition::new(old_field, &mono_reader);
+
+    // left over from another class that was at this address before a reload
+    mono_reader.cache_field(class.address(), "Old", "_count", old_field, &old_def);
+
+    let definition = TypeDefinition::new(class.address(), &mono_reader);
+    let (count, _) = definition.get_value("_count", object.as_ptr() as usize);
+    assert_eq!(mono_reader.read_i32(count), 3);
+
+    let (field, _) = mono_reader
+        .cached_field(class.address(), "Counter", "_count")
+        .unwrap();
+    assert_eq!(field, definition.get_field("_count").0);
+    assert_ne!(field, old_field);
+}
+
+#[test]
+fn test_first_field_after_object_header() {
+    let first = constants::OBJECT_HEADER_SIZE as i32;
+    let card = fake_mono::FakeClass::new("Card", &[("grpId", TypeCode::I4 as u8, first)]);
+    let point =
+        fake_mono::FakeClass::new("Point", &[("x", TypeCode::I4 as u8, first)]).value_type();
+
+    let object: [usize; 3] = [card.vtable(), 0, 7];
+    let inline_struct: [usize; 1] = [9];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    for (class, base, value) in [
+        (&card, object.as_ptr() as usize, 7),
+        (&point, inline_struct.as_ptr() as usize, 9),
+    ] {
+        let class = TypeDefinition::new(class.address(), &mono_reader);
+        let field = FieldDefinition::new(class.get_fields()[0], &mono_reader);
+        assert_eq!(
+            mono_reader.read_i32(class.field_address(&field, base)),
+            value
+        );
+    }
+}
+
+#[test]
+fn test_type_info_attrs() {
+    let i4 = (TypeCode::I4 as u32) << constants::TYPE_CODE_SHIFT;
+    let instance: [usize; 2] = [0, i4 as usize];
+    let static_field: [usize; 2] = [0, (i4 | constants::FIELD_ATTRIBUTE_STATIC) as usize];
+    let const_field: [usize; 2] = [
+        0,
+        (i4 | constants::FIELD_ATTRIBUTE_STATIC | constants::FIELD_ATTRIBUTE_LITERAL) as usize,
+    ];
+
+    let mono_reader = MonoReader::new(std::process::id());
+
+    let type_info = TypeInfo::new(instance.as_ptr() as usize, &mono_reader);
+    assert_eq!((type_info.is_static, type_info.is_const), (false, false));
+    assert_eq!(type_info.type_code, TypeCode::I4 as u32);
+
+    let type_info = TypeInfo::new(static_field.as_ptr() as usize, &mono_reader);
+    assert_eq!((type_info.is_static, type_info.is_const), (true, false));
+    assert_eq!(type_info.type_code, TypeCode::I4 as u32);
+
+    let type_info = TypeInfo::new(const_field.as_ptr() as usize, &mono_reader);
+    assert_eq!((type_info.is_static, type_info.is_const), (true, true));
+    assert_eq!(type_info.type_code, TypeCode::I4 as u32);
+}
+
+#[test]
+fn test_type_info_is_reference() {
+    let list = fake_mono::FakeClass::new("List`1", &[]);
+    let pair = fake_mono::FakeClass::new("KeyValuePair`2", &[]).value_type();
+    // MonoGenericClass: container_class first
+    let list_generic = [list.address()];
+    let pair_generic = [pair.address()];
+
+    let code = |code: TypeCode| (code as usize) << constants::TYPE_CODE_SHIFT;
+    let types: [[usize; 2]; 4] = [
+        [0, code(TypeCode::STRING)],
+        [0, code(TypeCode::I4)],
+        [list_generic.as_ptr() as usize, code(TypeCode::GENERICINST)],
+        [pair_generic.as_ptr() as usize, code(TypeCode::GENERICINST)],
+    ];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let is_reference: Vec<bool> = types
+        .iter()
+        .map(|t| TypeInfo::new(t.as_ptr() as usize, &mono_reader).is_reference(&mono_reader))
+        .collect();
+
+    assert_eq!(is_reference, vec![true, false, true, false]);
+
+    let resolved = |t: &[usize; 2]| {
+        TypeInfo::new(t.as_ptr() as usize, &mono_reader)
+            .resolve_class(&mono_reader)
+            .map(|class| class.name)
+    };
+    assert_eq!(resolved(&types[1]), None);
+    assert_eq!(resolved(&types[2]), Some("List`1".to_string()));
+    assert_eq!(resolved(&types[3]), Some("KeyValuePair`2".to_string()));
+}
+
+#[test]
+fn test_get_static_value_offsets() {
+    // static int first; static int second; in the same static storage
+    let class = fake_mono::FakeClass::with_statics(
+        "Statics",
+        &[],
+        &[
+            ("first", TypeCode::I4 as u8, 0),
+            ("second", TypeCode::I4 as u8, 8),
+        ],
+        &[11, 22],
+    );
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let definition = TypeDefinition::new(class.address(), &mono_reader);
+
+    let (first, _) = definition.get_static_value("first").unwrap();
+    let (second, _) = definition.get_static_value("second").unwrap();
+    assert_ne!(first, second);
+    assert_eq!(mono_reader.read_i32(first), 11);
+    assert_eq!(mono_reader.read_i32(second), 22);
+
+    // the fake vtable has no method slots, the storage pointer follows the header
+    let base = definition.static_storage_base();
+    assert_eq!(base, class.vtable() + constants::V_TABLE as usize);
+    assert_eq!(first, mono_reader.read_ptr(base));
+    assert_eq!(second, mono_reader.read_ptr(base) + 8);
+
+    assert!(matches!(
+        definition.get_static_value("third"),
+        Err(type_definition::StaticValueError::FieldNotFound)
+    ));
+}
+
+#[test]
+fn test_get_singleton_instance() {
+    let other = fake_mono::FakeClass::new("Other", &[]);
+    let other_object: [usize; 2] = [other.vtable(), 0];
+
+    // the backing field of an Instance property
+    let mut class = fake_mono::FakeClass::with_statics(
+        "Controller",
+        &[],
+        &[("<Instance>k__BackingField", TypeCode::CLASS as u8, 0)],
+        &[0],
+    );
+    let object: [usize; 2] = [class.vtable(), 0];
+
+    let mono_reader = MonoReader::new(std::process::id());
+
+    // not set yet
+    let definition = TypeDefinition::new(class.address(), &mono_reader);
+    assert_eq!(definition.get_singleton_instance(), None);
+
+    // holding an object of another class
+    class.set_static(0, other_object.as_ptr() as usize);
+    assert_eq!(definition.get_singleton_instance(), None);
+
+    class.set_static(0, object.as_ptr() as usize);
+    assert_eq!(
+        definition.get_singleton_instance(),
+        Some(object.as_ptr() as usize)
+    );
+}
+
+#[test]
+fn test_find_singletons() {
+    let other = fake_mono::FakeClass::new("Other", &[]);
+    let mut manager = fake_mono::FakeClass::with_statics(
+        "Manager",
+        &[],
+        &[
+            ("<Instance>k__BackingField", TypeCode::CLASS as u8, 0),
+            ("_otherInstance", TypeCode::CLASS as u8, 8),
+            ("_staleInstance", TypeCode::CLASS as u8, 16),
+        ],
+        &[0, 0, 0],
+    )
+    .in_namespace("Core");
+    let plain = fake_mono::FakeClass::new("Plain", &[]);
+
+    let manager_object: [usize; 2] = [manager.vtable(), 0];
+    let other_object: [usize; 2] = [other.vtable(), 0];
+    // not an object, its "vtable" doesn't lead back to a class
+    let garbage: [usize; 2] = [manager.address(), 0];
+
+    manager.set_static(0, manager_object.as_ptr() as usize);
+    manager.set_static(1, other_object.as_ptr() as usize);
+    manager.set_static(2, garbage.as_ptr() as usize);
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let singletons = find_singletons_in(&mono_reader, &[plain.address(), manager.address()]);
+
+    assert_eq!(
+        singletons,
+        vec![
+            json!({
+                "class": "Core.Manager",
+                "field_name": "<Instance>k__BackingField",
+                "address": manager_object.as_ptr() as usize,
+                "type": "Core.Manager"
+            }),
+            json!({
+                "class": "Core.Manager",
+                "field_name": "_otherInstance",
+                "address": other_object.as_ptr() as usize,
+                "type": "Other"
+            })
+        ]
+    );
+}
+
+#[test]
+fn test_backing_field_names() {
+    use field_definition::backing_field_name;
+
+    assert_eq!(property_name("<Instance>k__BackingField"), Some("Instance"));
+    assert_eq!(property_name(&backing_field_name("Cards")), Some("Cards"));
+    assert_eq!(property_name("_instance"), None);
+    assert_eq!(property_name("<>k__BackingField"), None);
+    assert_eq!(property_name("<>4__this"), None);
+
+    assert!(is_compiler_generated("<Instance>k__BackingField"));
+    assert!(is_compiler_generated("<>4__this"));
+    assert!(!is_compiler_generated("_instance"));
+}
+
+#[test]
+fn test_find_def_parallel() {
+    let names = ["A", "B", "Target", "C", "Target", "D", "E"];
+    let classes: Vec<_> = names
+        .iter()
+        .map(|name| fake_mono::FakeClass::new(name, &[]))
+        .collect();
+    let defs: Vec<usize> = classes.iter().map(|class| class.address()).collect();
+
+    let pid = std::process::id();
+    assert_eq!(find_def_parallel(&defs, "Target", pid), Some(2));
+    assert_eq!(find_def_parallel(&defs, "E", pid), Some(6));
+    assert_eq!(find_def_parallel(&defs, "Missing", pid), None);
+
+    let mono_reader = MonoReader::new(pid);
+    assert_eq!(
+        get_def_by_name(&defs, "Target".to_string(), &mono_reader),
+        Some(&defs[2])
+    );
+}
+
+#[test]
+fn test_get_def_by_full_name() {
+    let classes = [
+        fake_mono::FakeClass::new("CardData", &[]).in_namespace("Wotc.Mtga"),
+        fake_mono::FakeClass::new("CardData", &[]).in_namespace("Wotc.Mtga.Cards"),
+    ];
+    let defs: Vec<usize> = classes.iter().map(|class| class.address()).collect();
+
+    let mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(
+        get_def_by_full_name(&defs, "CardData", Some("Wotc.Mtga.Cards"), &mono_reader),
+        Some(&defs[1])
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "CardData", Some("Wotc"), &mono_reader),
+        None
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "CardData", None, &mono_reader),
+        Some(&defs[0])
+    );
+}
+
+#[test]
+fn test_get_def_by_full_name_global_and_nested() {
+    let global = fake_mono::FakeClass::new("PAPA", &[]);
+    let outer = fake_mono::FakeClass::new("Deck", &[]).in_namespace("Wotc.Mtga");
+    // nested classes have no namespace of their own
+    let inner = fake_mono::FakeClass::new("Entry", &[]).nested_in(&outer);
+    let other = fake_mono::FakeClass::new("Entry", &[]).in_namespace("Wotc.Mtga");
+    let defs: Vec<usize> = [&global, &other, &outer, &inner]
+        .iter()
+        .map(|class| class.address())
+        .collect();
+
+    let mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(
+        get_def_by_full_name(&defs, "PAPA", Some(""), &mono_reader),
+        Some(&defs[0])
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "Deck/Entry", Some("Wotc.Mtga"), &mono_reader),
+        Some(&defs[3])
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "Deck+Entry", None, &mono_reader),
+        Some(&defs[3])
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "Entry", Some("Wotc.Mtga"), &mono_reader),
+        Some(&defs[1])
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "Card/Entry", None, &mono_reader),
+        None
+    );
+    assert_eq!(
+        get_def_by_full_name(&defs, "Entry", Some(""), &mono_reader),
+        None
+    );
+}
+
+#[test]
+fn test_get_nested_types() {
+    let outer = fake_mono::FakeClass::new("Outer", &[]);
+    let inner = fake_mono::FakeClass::new("Inner", &[]).nested_in(&outer);
+    let other = fake_mono::FakeClass::new("Other", &[]);
+
+    let defs = vec![outer.address(), inner.address(), other.address()];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let class = TypeDefinition::new(outer.address(), &mono_reader);
+    assert_eq!(class.get_nested_types(&defs), vec![inner.address()]);
+
+    let class = TypeDefinition::new(other.address(), &mono_reader);
+    assert!(class.get_nested_types(&defs).is_empty());
+}
+
+#[test]
+fn test_read_class_field_limits() {
+    // struct { int a; string name; int b; }
+    let class = fake_mono::FakeClass::new(
+        "Limits",
+        &[
+            ("a", TypeCode::I4 as u8, 0x10),
+            ("name", TypeCode::STRING as u8, 0x18),
+            ("b", TypeCode::I4 as u8, 0x20),
+        ],
+    )
+    .value_type();
+
+    let instance: [u64; 3] = [1, 0, 2];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+    assert_eq!(
+        to_json(class.to_string()),
+        json!({ "a": 1, "name": null, "b": 2 })
+    );
+
+    class.set_skip_pointers(true);
+    assert_eq!(to_json(class.to_string()), json!({ "a": 1, "b": 2 }));
+
+    class.set_max_fields(Some(1));
+    assert_eq!(to_json(class.to_string()), json!({ "a": 1 }));
+}
+
+#[test]
+fn test_read_64_bit_values() {
+    // struct { long small; ulong big; long negative; }
+    let class = fake_mono::FakeClass::new(
+        "Longs",
+        &[
+            ("small", TypeCode::I8 as u8, 0x10),
+            ("big", TypeCode::U8 as u8, 0x18),
+            ("negative", TypeCode::I8 as u8, 0x20),
+        ],
+    )
+    .value_type();
+
+    let instance: [u64; 3] = [5, (1 << 53) + 1, (-(1i64 << 60)) as u64];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+
+    let value = to_json(class.to_string());
+    assert_eq!(value["small"], json!(5));
+    assert_eq!(value["big"], json!("9007199254740993"));
+    assert_eq!(value["negative"], json!("-1152921504606846976"));
+}
+
+#[test]
+fn test_resolve_generic_field_type() {
+    // MonoGenericParam of T2: owner, num
+    let param: [usize; 2] = [0, 1];
+
+    // struct Pair<T1, T2> { T1 first; T2 second; } read as Pair<int, long>
+    let class = fake_mono::FakeClass::new(
+        "Pair`2",
+        &[
+            ("first", TypeCode::I4 as u8, 0x10),
+            ("second", TypeCode::VAR as u8, 0x18),
+        ],
+    )
+    .value_type()
+    .field_type_data(1, param.as_ptr() as usize);
+
+    let int_type: [usize; 2] = [0, (TypeCode::I4 as usize) << constants::TYPE_CODE_SHIFT];
+    let long_type: [usize; 2] = [0, (TypeCode::I8 as usize) << constants::TYPE_CODE_SHIFT];
+    let instance: [u64; 2] = [7, 1 << 40];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+    class.set_generic_type_args(vec![
+        TypeInfo::new(int_type.as_ptr() as usize, &mono_reader),
+        TypeInfo::new(long_type.as_ptr() as usize, &mono_reader),
+    ]);
+
+    assert_eq!(
+        to_json(class.to_string()),
+        json!({ "first": 7, "second": 1u64 << 40 })
+    );
+
+    let (_, type_info) = class.get_value("second", instance.as_ptr() as usize);
+    assert_eq!(type_info.type_code, TypeCode::I8 as u32);
+}
+
+#[test]
+fn test_read_boxed_value() {
+    let int32 = fake_mono::FakeClass::new("Int32", &[])
+        .value_type()
+        .byval_type(TypeCode::I4 as u8);
+    let boxed: [usize; 3] = [int32.vtable(), 0, 42];
+
+    let other = fake_mono::FakeClass::new("Other", &[]);
+    let object: [usize; 2] = [other.vtable(), 0];
+
+    // struct { object boxed; object reference; object empty; }
+    let class = fake_mono::FakeClass::new(
+        "Holder",
+        &[
+            ("boxed", TypeCode::OBJECT as u8, 0x10),
+            ("reference", TypeCode::OBJECT as u8, 0x18),
+            ("empty", TypeCode::OBJECT as u8, 0x20),
+        ],
+    )
+    .value_type();
+    let instance: [usize; 3] = [boxed.as_ptr() as usize, object.as_ptr() as usize, 0];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+
+    let value = to_json(class.to_string());
+    assert_eq!(
+        value["boxed"],
+        json!({ "type": "boxed", "value_type": "Int32", "value": 42 })
+    );
+    assert_eq!(value["reference"], json!(null));
+    assert_eq!(value["empty"], json!(null));
+}
+
+#[test]
+fn test_read_enum_underlying_width() {
+    let byte_enum =
+        fake_mono::FakeClass::new("Rarity", &[("value__", TypeCode::U1 as u8, 0x10)]).enum_type();
+    let long_enum =
+        fake_mono::FakeClass::new("Flags", &[("value__", TypeCode::I8 as u8, 0x10)]).enum_type();
+
+    // struct { Rarity rarity; Flags flags; }
+    let class = fake_mono::FakeClass::new(
+        "Holder",
+        &[
+            ("rarity", TypeCode::VALUETYPE as u8, 0x10),
+            ("flags", TypeCode::VALUETYPE as u8, 0x18),
+        ],
+    )
+    .value_type()
+    .field_type_data(0, byte_enum.address())
+    .field_type_data(1, long_enum.address());
+    // the bytes past the byte enum are not part of it
+    let instance: [usize; 2] = [0xab00c8, -(1i64 << 40) as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+
+    let value = to_json(class.to_string());
+    assert_eq!(value["rarity"], json!(200));
+    assert_eq!(value["flags"], json!(-(1i64 << 40)));
+}
+
+#[test]
+fn test_read_floats_and_unity_structs() {
+    let color = fake_mono::FakeClass::new("Color", &[])
+        .in_namespace("UnityEngine")
+        .value_type();
+    let vector = fake_mono::FakeClass::new("Vector3", &[])
+        .in_namespace("UnityEngine")
+        .value_type();
+
+    // struct { float alpha; double scale; float broken; Color color; Vector3 position; }
+    let class = fake_mono::FakeClass::new(
+        "Holder",
+        &[
+            ("alpha", TypeCode::R4 as u8, 0x10),
+            ("scale", TypeCode::R8 as u8, 0x18),
+            ("broken", TypeCode::R4 as u8, 0x20),
+            ("color", TypeCode::VALUETYPE as u8, 0x24),
+            ("position", TypeCode::VALUETYPE as u8, 0x34),
+        ],
+    )
+    .value_type()
+    .field_type_data(3, color.address())
+    .field_type_data(4, vector.address());
+
+    let floats: Vec<u8> = [
+        &1.5f32.to_le_bytes()[..],
+        &[0; 4],
+        &(-0.25f64).to_le_bytes(),
+        &f32::NAN.to_le_bytes(),
+    ]
+    .concat()
+    .into_iter()
+    .chain(
+        [1.0f32, 0.5, 0.25, 1.0, 3.0, -2.0, 0.5]
+            .iter()
+            .flat_map(|v| v.to_le_bytes()),
+    )
+    .collect();
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(floats.as_ptr() as usize);
+
+    let value = to_json(class.to_string());
+    assert_eq!(value["alpha"], json!(1.5));
+    assert_eq!(value["scale"], json!(-0.25));
+    assert_eq!(value["broken"], json!(null));
+    assert_eq!(
+        value["color"],
+        json!({ "r": 1.0, "g": 0.5, "b": 0.25, "a": 1.0 })
+    );
+    assert_eq!(value["position"], json!({ "x": 3.0, "y": -2.0, "z": 0.5 }));
+}
+
+#[test]
+fn test_read_instance_field_by_runtime_class() {
+    let string = fake_mono::FakeClass::new("String", &[])
+        .in_namespace("System")
+        .byval_type(TypeCode::STRING as u8);
+    // MonoString: object header, length, then the UTF-16 chars
+    let text: [usize; 3] = [
+        string.vtable(),
+        0,
+        2 | ('h' as usize) << 32 | ('i' as usize) << 48,
+    ];
+
+    let card = fake_mono::FakeClass::new("Card", &[("id", TypeCode::I4 as u8, 0x10)])
+        .byval_type(TypeCode::CLASS as u8);
+    let cards = fake_mono::FakeClass::new("Card[]", &[])
+        .array_of(&card)
+        .element_size(constants::SIZE_OF_PTR)
+        .byval_type(TypeCode::SZARRAY as u8);
+    let element: [usize; 3] = [card.vtable(), 0, 7];
+    let mut array = fake_mono::fake_array(1, &(element.as_ptr() as usize).to_le_bytes());
+    array[..8].copy_from_slice(&cards.vtable().to_le_bytes());
+
+    // struct { object text; IEnumerable items; }
+    let class = fake_mono::FakeClass::new(
+        "Holder",
+        &[
+            ("text", TypeCode::OBJECT as u8, 0x10),
+            ("items", TypeCode::CLASS as u8, 0x18),
+        ],
+    )
+    .value_type();
+    let instance: [usize; 2] = [text.as_ptr() as usize, array.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(class.address(), &mono_reader);
+    class.set_fields_base(instance.as_ptr() as usize);
+    class.set_depth(1);
+
+    let value = to_json(class.to_string());
+    assert_eq!(value["text"], json!("hi"));
+    assert_eq!(value["items"], json!([{ "id": 7 }]));
+
+    let location = [text.as_ptr() as usize];
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+    assert_eq!(
+        to_json(managed.read_instance_field(&TypeInfo::from_code(TypeCode::OBJECT as u32), 0)),
+        json!("hi")
+    );
+}
+
+#[test]
+fn test_read_result_from_json() {
+    assert_eq!(ReadResult::from(json!(3)), ReadResult::Primitive(json!(3)));
+    assert_eq!(
+        ReadResult::from(json!([1, 2])),
+        ReadResult::Array(vec![json!(1), json!(2)])
+    );
+    assert_eq!(
+        ReadResult::from(json!({ "error": "Process not found" })),
+        ReadResult::Error("Process not found".to_string())
+    );
+
+    // a class with a field named error is still an object
+    let object = json!({ "error": 1, "code": 2 });
+    assert!(matches!(
+        ReadResult::from(object.clone()),
+        ReadResult::Object(_)
+    ));
+    assert_eq!(ReadResult::from(object.clone()).to_json(), object);
+
+    let pairs = ReadResult::Dictionary(vec![(json!(1), json!("a"))]);
+    assert_eq!(pairs.to_json(), json!([{ "key": 1, "value": "a" }]));
+}
+
+#[test]
+fn test_read_managed_string() {
+    // MonoString: object header, length, UTF-16 chars
+    let mut string = vec![0u8; constants::STRING_LENGTH];
+    string.extend_from_slice(&5u32.to_le_bytes());
+    for c in "Llano".encode_utf16() {
+        string.extend_from_slice(&c.to_le_bytes());
+    }
+
+    let mut empty = vec![0u8; constants::STRING_LENGTH];
+    empty.extend_from_slice(&0u32.to_le_bytes());
+
+    // the fields holding the string references
+    let fields: [usize; 3] = [string.as_ptr() as usize, empty.as_ptr() as usize, 0];
+    let field_addr = |i: usize| fields.as_ptr() as usize + i * constants::SIZE_OF_PTR;
+
+    let mono_reader = MonoReader::new(std::process::id());
+
+    let value = Managed::new(&mono_reader, field_addr(0), None).read_managed_string();
+    assert_eq!(value.to_json(), json!("Llano"));
+
+    let value = Managed::new(&mono_reader, field_addr(1), None).read_managed_string();
+    assert_eq!(value.to_json(), json!(""));
+
+    let value = Managed::new(&mono_reader, field_addr(2), None).read_managed_string();
+    assert_eq!(value.to_json(), json!(null));
+
+    let value = Managed::new(&mono_reader, 0x8, None).read_managed_string();
+    assert_eq!(value.to_json(), json!({ "type": "unreadable" }));
+}
+
+#[test]
+fn test_search_fields() {
+    let root = fake_mono::FakeClass::new("Root", &[("_manager", TypeCode::CLASS as u8, 0x10)]);
+    let manager = fake_mono::FakeClass::new(
+        "Manager",
+        &[
+            ("<Cards>k__BackingField", TypeCode::CLASS as u8, 0x10),
+            ("_root", TypeCode::CLASS as u8, 0x18),
+        ],
+    );
+
+    // the manager points back to the root
+    let mut root_object = Box::new([root.vtable(), 0, 0]);
+    let manager_object: [usize; 4] = [manager.vtable(), 0, 0, root_object.as_ptr() as usize];
+    root_object[2] = manager_object.as_ptr() as usize;
+    let location = [root_object.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let location = location.as_ptr() as usize;
+
+    assert_eq!(
+        search_fields_in(&mono_reader, location, "cards", 4),
+        vec!["_manager.<Cards>k__BackingField".to_string()]
+    );
+    assert!(search_fields_in(&mono_reader, location, "cards", 0).is_empty());
+    assert_eq!(
+        search_fields_in(&mono_reader, location, "_", 1),
+        vec![
+            "_manager".to_string(),
+            "_manager.<Cards>k__BackingField".to_string(),
+            "_manager._root".to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_field_type_info() {
+    let class = fake_mono::FakeClass::with_statics(
+        "Card",
+        &[("grpId", TypeCode::U4 as u8, 0x10)],
+        &[("Count", TypeCode::I4 as u8, 0)],
+        &[0],
+    );
+
+    let mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(
+        field_type_info(&mono_reader, class.address(), "grpId"),
+        json!({
+            "attrs": (TypeCode::U4 as u32) << constants::TYPE_CODE_SHIFT,
+            "type_code": TypeCode::U4 as u32,
+            "type": "UINT (U4)",
+            "data": 0,
+            "is_static": false,
+            "is_const": false
+        })
+    );
+
+    let count = field_type_info(&mono_reader, class.address(), "Count");
+    assert_eq!(count["is_static"], json!(true));
+    assert_eq!(
+        field_type_info(&mono_reader, class.address(), "missing"),
+        json!({ "error": "Field not found: missing" })
+    );
+}
+
+#[test]
+fn test_read_account() {
+    let class = fake_mono::FakeClass::new(
+        "AccountInformation",
+        &[
+            ("<AccountID>k__BackingField", TypeCode::STRING as u8, 0x10),
+            ("<DisplayName>k__BackingField", TypeCode::STRING as u8, 0x18),
+            ("<PersonaID>k__BackingField", TypeCode::STRING as u8, 0x20),
+        ],
+    );
+
+    let managed_string = |value: &str| {
+        let mut string = vec![0u8; constants::STRING_LENGTH];
+        string.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        for c in value.encode_utf16() {
+            string.extend_from_slice(&c.to_le_bytes());
+        }
+        string
+    };
+    let account_id = managed_string("ABCD1234");
+    let display_name = managed_string("Llanowar#12345");
+
+    let account: [usize; 5] = [
+        class.vtable(),
+        0,
+        account_id.as_ptr() as usize,
+        display_name.as_ptr() as usize,
+        0,
+    ];
+    // created but not filled in yet
+    let empty_account: [usize; 5] = [class.vtable(), 0, 0, 0, 0];
+    let locations = [
+        account.as_ptr() as usize,
+        empty_account.as_ptr() as usize,
+        0,
+    ];
+    let location = |index: usize| locations.as_ptr() as usize + index * constants::SIZE_OF_PTR;
+
+    let mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(
+        read_account_at(&mono_reader, location(0)),
+        json!({ "screen_name": "Llanowar#12345", "user_id": "ABCD1234" })
+    );
+
+    assert_eq!(
+        read_account_at(&mono_reader, location(1)),
+        json!({ "error": "Not logged in" })
+    );
+    assert_eq!(
+        read_account_at(&mono_reader, location(2)),
+        json!({ "error": "Not logged in" })
+    );
+
+    // not the AccountInformation of this game version
+    let other_class = fake_mono::FakeClass::new(
+        "AccountInformation",
+        &[("<DisplayName>k__BackingField", TypeCode::STRING as u8, 0x10)],
+    );
+    let other_account: [usize; 3] = [other_class.vtable(), 0, display_name.as_ptr() as usize];
+    let other_location = [other_account.as_ptr() as usize];
+    assert_eq!(
+        read_account_at(&mono_reader, other_location.as_ptr() as usize),
+        json!({ "error": "Field not found: AccountID" })
+    );
+}
+
+#[test]
+fn test_read_struct_array() {
+    let point = fake_mono::FakeClass::new(
+        "Point",
+        &[
+            ("x", TypeCode::I4 as u8, 0x10),
+            ("y", TypeCode::I4 as u8, 0x14),
+        ],
+    )
+    .value_type()
+    .instance_size(0x18)
+    .byval_type(TypeCode::VALUETYPE as u8);
+    // the array class element size is a pointer, the struct stride is 8
+    let points = fake_mono::FakeClass::new("Point[]", &[])
+        .array_of(&point)
+        .element_size(constants::SIZE_OF_PTR);
+
+    let elements: Vec<u8> = [1i32, 2, 3, 4]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut array = fake_mono::fake_array(2, &elements);
+    array[..8].copy_from_slice(&points.vtable().to_le_bytes());
+    let location = [array.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        to_json(managed.read_managed_array()),
+        json!([{ "x": 1, "y": 2 }, { "x": 3, "y": 4 }])
+    );
+    assert_eq!(
+        to_json(managed.read_managed_array_range(1, 5, 1)),
+        json!([{ "x": 3, "y": 4 }])
+    );
+}
+
+#[test]
+fn test_read_generic_struct_array() {
+    // struct Pair<TKey, TValue> as Pair<int, int>, stored inline in the array
+    // without the object header its field offsets count
+    let pair = fake_mono::FakeClass::new(
+        "Pair`2",
+        &[
+            ("key", TypeCode::I4 as u8, 0x10),
+            ("value", TypeCode::I4 as u8, 0x14),
+        ],
+    )
+    .value_type();
+    // MonoGenericClass: container_class first
+    let pair_generic = [pair.address(), 0];
+    let pair_instance = fake_mono::FakeClass::new("Pair`2", &[])
+        .value_type()
+        .generic_instance_of(pair_generic.as_ptr() as usize);
+    let pairs = fake_mono::FakeClass::new("Pair`2[]", &[])
+        .array_of(&pair_instance)
+        .element_size(8);
+
+    // reading the fields at their offsets from each element would land on
+    // the next elements and the zeroes past the last one
+    let elements: Vec<u8> = [1i32, 2, 3, 4, 0, 0, 0, 0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut array = fake_mono::fake_array(2, &elements);
+    array[..8].copy_from_slice(&pairs.vtable().to_le_bytes());
+    let location = [array.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        to_json(managed.read_managed_array()),
+        json!([{ "key": 1, "value": 2 }, { "key": 3, "value": 4 }])
+    );
+}
+
+#[test]
+fn test_read_deck() {
+    let entry = fake_mono::FakeClass::new(
+        "CardInDeck",
+        &[
+            ("grpId", TypeCode::U4 as u8, 0x10),
+            ("count", TypeCode::I4 as u8, 0x14),
+        ],
+    )
+    .value_type()
+    .instance_size(0x18)
+    .byval_type(TypeCode::VALUETYPE as u8);
+    let entries = fake_mono::FakeClass::new("CardInDeck[]", &[])
+        .array_of(&entry)
+        .element_size(constants::SIZE_OF_PTR);
+    let list = fake_mono::FakeClass::new(
+        "List`1",
+        &[
+            ("_items", TypeCode::SZARRAY as u8, 0x10),
+            ("_size", TypeCode::I4 as u8, 0x18),
+        ],
+    );
+
+    // the backing array has room for a third entry past _size
+    let elements: Vec<u8> = [70123u32, 4, 512, 1, 99, 99]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut array = fake_mono::fake_array(3, &elements);
+    array[..8].copy_from_slice(&entries.vtable().to_le_bytes());
+    let list_object: [usize; 4] = [list.vtable(), 0, array.as_ptr() as usize, 2];
+    let location = [list_object.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    assert_eq!(
+        read_deck_with_reader(&mono_reader, location.as_ptr() as i64),
+        json!([{ "grpId": 70123, "count": 4 }, { "grpId": 512, "count": 1 }])
+    );
+
+    let empty = [0usize];
+    assert_eq!(
+        read_deck_with_reader(&mono_reader, empty.as_ptr() as i64),
+        json!({ "error": "Deck card list could not be read" })
+    );
+}
+
+#[test]
+fn test_read_class_expanded() {
+    let card = fake_mono::FakeClass::new(
+        "CardInDeck",
+        &[
+            ("grpId", TypeCode::U4 as u8, 0x10),
+            ("count", TypeCode::I4 as u8, 0x14),
+        ],
+    )
+    .value_type()
+    .instance_size(0x18)
+    .byval_type(TypeCode::VALUETYPE as u8);
+    let cards_class = fake_mono::FakeClass::new("CardInDeck[]", &[])
+        .array_of(&card)
+        .element_size(constants::SIZE_OF_PTR);
+    let list_class = fake_mono::FakeClass::new(
+        "List`1",
+        &[
+            ("_items", TypeCode::SZARRAY as u8, 0x10),
+            ("_size", TypeCode::I4 as u8, 0x18),
+        ],
+    );
+    let card_bytes: Vec<u8> = [1u32, 4, 2, 3, 3, 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut cards = fake_mono::fake_array(3, &card_bytes);
+    cards[..8].copy_from_slice(&cards_class.vtable().to_le_bytes());
+    let list: [usize; 4] = [list_class.vtable(), 0, cards.as_ptr() as usize, 3];
+
+    // Dictionary<int, int> with its Entry class
+    let dictionary_class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+    let entry = fake_mono::FakeClass::new(
+        "Entry",
+        &[
+            ("hashCode", TypeCode::I4 as u8, 0x10),
+            ("next", TypeCode::I4 as u8, 0x14),
+            ("key", TypeCode::I4 as u8, 0x18),
+            ("value", TypeCode::I4 as u8, 0x1c),
+        ],
+    )
+    .value_type()
+    .instance_size(0x20)
+    .byval_type(TypeCode::VALUETYPE as u8);
+    let entries_class = fake_mono::FakeClass::new("Entry[]", &[])
+        .array_of(&entry)
+        .element_size(constants::SIZE_OF_PTR);
+    let entry_bytes: Vec<u8> = [5i32, -1, 5, 50, 6, -1, 6, 60, 7, -1, 7, 70]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut entries = fake_mono::fake_array(3, &entry_bytes);
+    entries[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+    let dictionary: [usize; 5] = [
+        dictionary_class.vtable(),
+        0,
+        0,
+        entries.as_ptr() as usize,
+        3,
+    ];
+
+    let inventory = fake_mono::FakeClass::new(
+        "Inventory",
+        &[
+            ("cards", TypeCode::GENERICINST as u8, 0x10),
+            ("gems", TypeCode::I4 as u8, 0x18),
+            ("boosters", TypeCode::GENERICINST as u8, 0x20),
+        ],
+    );
+    let object: [usize; 5] = [
+        inventory.vtable(),
+        0,
+        list.as_ptr() as usize,
+        1500,
+        dictionary.as_ptr() as usize,
+    ];
+    let location = [object.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let read = |max_entries| {
+        read_class_with_reader(
+            &mono_reader,
+            location.as_ptr() as i64,
+            None,
+            None,
+            max_entries,
+            None,
+        )
+    };
+
+    assert_eq!(
+        read(Some(2)),
+        json!({
+            "cards": {
+                "count": 3,
+                "entries": [{ "grpId": 1, "count": 4 }, { "grpId": 2, "count": 3 }]
+            },
+            "gems": 1500,
+            "boosters": {
+                "count": 3,
+                "entries": [{ "key": 5, "value": 50 }, { "key": 6, "value": 60 }]
+            }
+        })
+    );
+
+    assert_eq!(
+        read(Some(0))["boosters"],
+        json!({ "count": 3, "entries": [] })
+    );
+
+    // without it the collections stay as they were
+    assert_eq!(read(None)["gems"], json!(1500));
+    assert!(read(None)["cards"].get("entries").is_none());
+}
+
+#[test]
+fn test_read_dict_entries() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+
+    // Entry<uint, int>: hashCode, next, key, value. The second one was
+    // removed and the last one is past _count.
+    let entries: [i32; 16] = [
+        70, -1, 70, 4, //
+        -1, -1, 71, 1, //
+        72, 0, 72, 2, //
+        0, 0, 0, 0,
+    ];
+    let entry_bytes: Vec<u8> = entries.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let array = fake_mono::fake_array(4, &entry_bytes);
+
+    // object header, _buckets, _entries, _count
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 3];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        managed.read_dict_entries(None),
+        Some(vec![(70, 4), (72, 2)])
+    );
+
+    let modern = &managed::DICTIONARY_LAYOUTS[0];
+    let legacy = &managed::DICTIONARY_LAYOUTS[1];
+    assert_eq!(
+        managed.read_dict_entries(Some(modern)),
+        Some(vec![(70, 4), (72, 2)])
+    );
+    assert_eq!(managed.read_dict_entries(Some(legacy)), None);
+}
+
+#[test]
+fn test_read_dict_entries_short_element_class() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+    // an element size too small for Entry<uint, int>, the entries are
+    // still read 16 bytes apart
+    let entries_class = fake_mono::FakeClass::new("Entry[]", &[]).element_size(12);
+
+    let entries: [i32; 8] = [
+        70, -1, 70, 4, //
+        72, -1, 72, 2,
+    ];
+    let entry_bytes: Vec<u8> = entries.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let mut array = fake_mono::fake_array(2, &entry_bytes);
+    array[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 2];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        managed.read_dict_entries(None),
+        Some(vec![(70, 4), (72, 2)])
+    );
+
+    let int_type = type_info_by_name("I4").unwrap();
+    assert_eq!(
+        managed.read_dict_typed(&int_type, &int_type),
+        Some(vec![(json!(70), json!(4)), (json!(72), json!(2))])
+    );
+}
+
+#[test]
+fn test_read_dict_skips_free_list() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+            ("_freeList", TypeCode::I4 as u8, 0x24),
+            ("_freeCount", TypeCode::I4 as u8, 0x28),
+        ],
+    );
+    let entries_class = fake_mono::FakeClass::new("Entry[]", &[]).element_size(16);
+
+    // corefx removes entries without touching their hash code, the free
+    // list is 3 then 1, linked as StartOfFreeList (-3) - next
+    let entries: [i32; 16] = [
+        70, -1, 70, 4, //
+        71, -2, 71, 1, //
+        72, -1, 72, 2, //
+        73, -4, 73, 3,
+    ];
+    let entry_bytes: Vec<u8> = entries.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let mut array = fake_mono::fake_array(4, &entry_bytes);
+    array[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+
+    // object header, _buckets, _entries, _count and _freeList, _freeCount
+    let dictionary: [usize; 6] = [
+        class.vtable(),
+        0,
+        0,
+        array.as_ptr() as usize,
+        4 | 3 << 32,
+        2,
+    ];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        managed.read_dict_entries(None),
+        Some(vec![(70, 4), (72, 2)])
+    );
+
+    let int_type = type_info_by_name("I4").unwrap();
+    assert_eq!(
+        managed.read_dict_typed(&int_type, &int_type),
+        Some(vec![(json!(70), json!(4)), (json!(72), json!(2))])
+    );
+}
+
+#[test]
+fn test_read_nested_dictionary() {
+    let dictionary_class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+    let entry_fields = |value_type: TypeCode, value_offset: i32| {
+        [
+            ("hashCode", TypeCode::I4 as u8, 0x10),
+            ("next", TypeCode::I4 as u8, 0x14),
+            ("key", TypeCode::I4 as u8, 0x18),
+            ("value", value_type as u8, value_offset),
+        ]
+    };
+
+    // Dictionary<int, int>.Entry, 16 bytes
+    let inner_entry = fake_mono::FakeClass::new("Entry", &entry_fields(TypeCode::I4, 0x1c))
+        .value_type()
+        .instance_size(0x20)
+        .byval_type(TypeCode::VALUETYPE as u8);
+    let inner_entries_class = fake_mono::FakeClass::new("Entry[]", &[])
+        .array_of(&inner_entry)
+        .element_size(constants::SIZE_OF_PTR);
+    let inner_bytes: Vec<u8> = [5i32, -1, 5, 50, 6, -1, 6, 60]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut inner_entries = fake_mono::fake_array(2, &inner_bytes);
+    inner_entries[..8].copy_from_slice(&inner_entries_class.vtable().to_le_bytes());
+    let inner: [usize; 5] = [
+        dictionary_class.vtable(),
+        0,
+        0,
+        inner_entries.as_ptr() as usize,
+        2,
+    ];
+
+    // Dictionary<int, Dictionary<int, int>>.Entry, 24 bytes
+    let outer_entry = fake_mono::FakeClass::new("Entry", &entry_fields(TypeCode::CLASS, 0x20))
+        .value_type()
+        .instance_size(0x28)
+        .byval_type(TypeCode::VALUETYPE as u8);
+    let outer_entries_class = fake_mono::FakeClass::new("Entry[]", &[])
+        .array_of(&outer_entry)
+        .element_size(constants::SIZE_OF_PTR);
+    let mut outer_bytes = Vec::new();
+    for (key, value) in [(1i32, inner.as_ptr() as usize), (2, 0)] {
+        outer_bytes.extend_from_slice(&key.to_le_bytes());
+        outer_bytes.extend_from_slice(&(-1i32).to_le_bytes());
+        outer_bytes.extend_from_slice(&key.to_le_bytes());
+        outer_bytes.extend_from_slice(&0i32.to_le_bytes());
+        outer_bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    let mut outer_entries = fake_mono::fake_array(2, &outer_bytes);
+    outer_entries[..8].copy_from_slice(&outer_entries_class.vtable().to_le_bytes());
+    let outer: [usize; 5] = [
+        dictionary_class.vtable(),
+        0,
+        0,
+        outer_entries.as_ptr() as usize,
+        2,
+    ];
+    let location = [outer.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    let mut budget = 10;
+    let pairs = managed.read_dict_nested(1, &mut budget).unwrap();
+    assert_eq!(
+        pairs,
+        vec![
+            (
+                json!(1),
+                json!([{ "key": 5, "value": 50 }, { "key": 6, "value": 60 }])
+            ),
+            (json!(2), json!(null))
+        ]
+    );
+    assert_eq!(budget, 8);
+
+    // over budget the inner dictionary is left as read_dict_pairs has it
+    let mut budget = 1;
+    let pairs = managed.read_dict_nested(1, &mut budget).unwrap();
+    assert_eq!(pairs, managed.read_dict_pairs(None).unwrap());
+
+    // with the types given the inner dictionary is an address, or its
+    // entries with a depth
+    let int_type = type_info_by_name("I4").unwrap();
+    let class_type = type_info_by_name("CLASS").unwrap();
+    assert_eq!(
+        managed.read_dict_typed(&int_type, &class_type),
+        Some(vec![
+            (json!(1), json!(inner.as_ptr() as usize)),
+            (json!(2), json!(0))
+        ])
+    );
+    assert_eq!(
+        managed.read_dict_typed_expanded(&int_type, &class_type, 1),
+        Some(vec![
+            (
+                json!(1),
+                json!([{ "key": 5, "value": 50 }, { "key": 6, "value": 60 }])
+            ),
+            (json!(2), json!(null))
+        ])
+    );
+}
+
+#[test]
+fn test_read_dict_typed() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+
+    // Entry<int, long>: hashCode, next, key, (padding), value
+    let mut entry_bytes = Vec::new();
+    for (hash_code, key, value) in [(3i32, 3i32, 300i64), (-1, 4, 400), (5, 5, 1 << 60)] {
+        entry_bytes.extend_from_slice(&hash_code.to_le_bytes());
+        entry_bytes.extend_from_slice(&(-1i32).to_le_bytes());
+        entry_bytes.extend_from_slice(&key.to_le_bytes());
+        entry_bytes.extend_from_slice(&0i32.to_le_bytes());
+        entry_bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    let array = fake_mono::fake_array(3, &entry_bytes);
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 3];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    let key_type = type_info_by_name("I4").unwrap();
+    let value_type = type_info_by_name("I8").unwrap();
+    assert_eq!(
+        managed.read_dict_typed(&key_type, &value_type),
+        Some(vec![
+            (json!(3), json!(300)),
+            (json!(5), json!("1152921504606846976"))
+        ])
+    );
+
+    assert!(type_info_by_name("NOT_A_TYPE").is_none());
+}
+
+#[test]
+fn test_dict_entry_stride_from_array() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+    // entries of 24 bytes where 16 are expected, the array class knows
+    let entries_class = fake_mono::FakeClass::new("Entry[]", &[]).element_size(24);
+
+    let entries: [i32; 12] = [
+        70, -1, 70, 4, 0, 0, //
+        72, -1, 72, 2, 0, 0,
+    ];
+    let entry_bytes: Vec<u8> = entries.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let mut array = fake_mono::fake_array(2, &entry_bytes);
+    array[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 2];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        managed.read_dict_entries(None),
+        Some(vec![(70, 4), (72, 2)])
+    );
+
+    let int_type = type_info_by_name("I4").unwrap();
+    assert_eq!(
+        managed.read_dict_typed(&int_type, &int_type),
+        Some(vec![(json!(70), json!(4)), (json!(72), json!(2))])
+    );
+}
+
+#[test]
+fn test_read_dict_key_type_from_comparer() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_comparer", TypeCode::GENERICINST as u8, 0x20),
+        ],
+    );
+    let comparer_class = fake_mono::FakeClass::new("NonRandomizedStringEqualityComparer", &[]);
+    let comparer: [usize; 2] = [comparer_class.vtable(), 0];
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, 0, comparer.as_ptr() as usize];
+    // the default comparer may not be stored
+    let without_comparer: [usize; 5] = [class.vtable(), 0, 0, 0, 0];
+    let locations = [
+        dictionary.as_ptr() as usize,
+        without_comparer.as_ptr() as usize,
+    ];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = |index: usize| {
+        let location = locations.as_ptr() as usize + index * constants::SIZE_OF_PTR;
+        Managed::new(&mono_reader, location, None)
+    };
+
+    let key_type = managed(0).read_dict_key_type().unwrap();
+    assert_eq!(key_type.type_code, TypeCode::STRING as u32);
+    assert!(managed(1).read_dict_key_type().is_none());
+}
+
+#[test]
+fn test_read_concurrent_dictionary() {
+    let dictionary = fake_mono::FakeClass::new(
+        "ConcurrentDictionary`2",
+        &[("_tables", TypeCode::CLASS as u8, 0x10)],
+    );
+    let tables = fake_mono::FakeClass::new(
+        "Tables",
+        &[
+            ("_buckets", TypeCode::SZARRAY as u8, 0x10),
+            ("_countPerLock", TypeCode::SZARRAY as u8, 0x18),
+        ],
+    );
+    let node = fake_mono::FakeClass::new(
+        "Node",
+        &[
+            ("_key", TypeCode::I4 as u8, 0x10),
+            ("_value", TypeCode::I4 as u8, 0x14),
+            ("_next", TypeCode::CLASS as u8, 0x18),
+        ],
+    );
+
+    // the first bucket chains two nodes, the second one is empty
+    let third: [usize; 4] = [node.vtable(), 0, 3 | 30 << 32, 0];
+    let first: [usize; 4] = [node.vtable(), 0, 1 | 10 << 32, third.as_ptr() as usize];
+    let second: [usize; 4] = [node.vtable(), 0, 2 | 20 << 32, 0];
+    let bucket_bytes: Vec<u8> = [first.as_ptr() as usize, 0, second.as_ptr() as usize]
+        .iter()
+        .flat_map(|ptr| ptr.to_le_bytes())
+        .collect();
+    let buckets = fake_mono::fake_array(3, &bucket_bytes);
+    let count_bytes: Vec<u8> = [2i32, 1].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let count_per_lock = fake_mono::fake_array(2, &count_bytes);
+    let tables_object: [usize; 4] = [
+        tables.vtable(),
+        0,
+        buckets.as_ptr() as usize,
+        count_per_lock.as_ptr() as usize,
+    ];
+    let dictionary_object: [usize; 3] = [dictionary.vtable(), 0, tables_object.as_ptr() as usize];
+    let location = [dictionary_object.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    let expected = vec![
+        (json!(1), json!(10)),
+        (json!(3), json!(30)),
+        (json!(2), json!(20)),
+    ];
+    assert_eq!(managed.read_concurrent_dict_pairs(), Some(expected.clone()));
+    assert_eq!(managed.read_dict_pairs(None), Some(expected));
+
+    // the preview stops walking at its limit, the count comes from the locks
+    assert_eq!(
+        to_json(managed.read_collection_preview(2).unwrap()),
+        json!({
+            "count": 3,
+            "entries": [{ "key": 1, "value": 10 }, { "key": 3, "value": 30 }]
+        })
+    );
+}
+
+#[test]
+fn test_dictionary_entry_layout() {
+    use managed::dictionary_entry_layout;
+
+    let layout = |key, value| dictionary_entry_layout(key, value).unwrap();
+    assert_eq!(layout(TypeCode::U4, TypeCode::I4), (8, 12, 16));
+    assert_eq!(layout(TypeCode::I4, TypeCode::I8), (8, 16, 24));
+    assert_eq!(layout(TypeCode::I8, TypeCode::I4), (8, 16, 24));
+    assert_eq!(layout(TypeCode::U1, TypeCode::BOOLEAN), (8, 9, 12));
+    assert_eq!(layout(TypeCode::I2, TypeCode::R8), (8, 16, 24));
+    assert_eq!(layout(TypeCode::STRING, TypeCode::I4), (8, 16, 24));
+    assert_eq!(
+        dictionary_entry_layout(TypeCode::VALUETYPE, TypeCode::I4),
+        None
+    );
+}
+
+#[test]
+fn test_read_dict_raw() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("_entries", TypeCode::SZARRAY as u8, 0x18),
+            ("_count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+    let entries_class = fake_mono::FakeClass::new("Entry[]", &[]).element_size(12);
+
+    // hashCode, next, then an unknown 4 byte payload
+    let entry_bytes: Vec<u8> = [[7, -1, 0x0102_0304], [-1, -1, 0]]
+        .iter()
+        .flatten()
+        .flat_map(|v: &i32| v.to_le_bytes())
+        .collect();
+    let mut array = fake_mono::fake_array(2, &entry_bytes);
+    array[..8].copy_from_slice(&entries_class.vtable().to_le_bytes());
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 2];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(
+        managed.read_dict_raw(),
+        Some(vec![
+            json!({ "hashCode": 7, "next": -1, "raw": "04030201" }),
+            json!({ "hashCode": -1, "next": -1, "raw": "00000000" })
+        ])
+    );
+}
+
+#[test]
+fn test_detect_legacy_dictionary_layout() {
+    let class = fake_mono::FakeClass::new(
+        "Dictionary`2",
+        &[
+            ("entries", TypeCode::SZARRAY as u8, 0x18),
+            ("count", TypeCode::I4 as u8, 0x20),
+        ],
+    );
+
+    let entries: [i32; 4] = [7, -1, 7, 1];
+    let entry_bytes: Vec<u8> = entries.iter().flat_map(|v| v.to_le_bytes()).collect();
+    let array = fake_mono::fake_array(1, &entry_bytes);
+
+    let dictionary: [usize; 5] = [class.vtable(), 0, 0, array.as_ptr() as usize, 1];
+    let location = [dictionary.as_ptr() as usize];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, location.as_ptr() as usize, None);
+
+    assert_eq!(managed.read_dict_entries(None), Some(vec![(7, 1)]));
+}
+
+#[test]
+fn test_read_utf8_class_name() {
+    let mono_reader = MonoReader::new(std::process::id());
+
+    let ascii = std::ffi::CString::new("CardDatabase").unwrap();
+    assert_eq!(
+        mono_reader.read_ascii_string(ascii.as_ptr() as usize),
+        "CardDatabase"
+    );
+
+    let utf8 = std::ffi::CString::new("Карта\u{fffd}").unwrap();
+    assert_eq!(
+        mono_reader.maybe_read_ascii_string(utf8.as_ptr() as usize),
+        Some("Карта\u{fffd}".to_string())
+    );
+
+    let invalid = [b'a', 0xff, b'b', 0];
+    assert_eq!(
+        mono_reader.read_ascii_string(invalid.as_ptr() as usize),
+        "a\u{fffd}b"
+    );
+}
+
+#[test]
+fn test_read_decimal() {
+    assert_eq!(managed::format_decimal(2 << 16, 0, 1234, 0), "12.34");
+    assert_eq!(
+        managed::format_decimal(0x8000_0000 | 3 << 16, 0, 5, 0),
+        "-0.005"
+    );
+    assert_eq!(managed::format_decimal(0, 0, 42, 0), "42");
+    assert_eq!(
+        managed::format_decimal(0, u32::MAX, u32::MAX, u32::MAX),
+        "79228162514264337593543950335"
+    );
+
+    let decimal = fake_mono::FakeClass::new(
+        "Decimal",
+        &[
+            ("flags", TypeCode::I4 as u8, 0x10),
+            ("hi", TypeCode::I4 as u8, 0x14),
+            ("lo", TypeCode::I4 as u8, 0x18),
+            ("mid", TypeCode::I4 as u8, 0x1c),
+        ],
+    )
+    .in_namespace("System")
+    .value_type();
+    let decimal_type: [usize; 2] = [
+        decimal.address(),
+        (TypeCode::VALUETYPE as usize) << constants::TYPE_CODE_SHIFT,
+    ];
+
+    // 1 << 32 | 1 scaled by 2
+    let value: [u32; 4] = [2 << 16, 0, 1, 1];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let managed = Managed::new(&mono_reader, value.as_ptr() as usize, None);
+    let type_info = TypeInfo::new(decimal_type.as_ptr() as usize, &mono_reader);
+
+    let json: serde_json::Value = serde_json::from_str(&managed.read_value(type_info)).unwrap();
+    assert_eq!(json, json!({ "type": "decimal", "value": "42949672.97" }));
+}
+
+#[test]
+fn test_calibrate_field_offsets() {
+    let class = fake_mono::FakeClass::new(
+        "Card",
+        &[
+            ("grpId", TypeCode::I4 as u8, 0x10),
+            ("<Count>k__BackingField", TypeCode::I4 as u8, 0x14),
+            ("title", TypeCode::STRING as u8, 0x18),
+        ],
+    );
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let expected = vec!["grpId".to_string(), "title".to_string()];
+    let offsets = calibrate::find_field_offsets(&mono_reader, class.address(), &expected).unwrap();
+
+    assert_eq!(offsets.fields, constants::TYPE_DEFINITION_FIELDS as usize);
+    assert_eq!(
+        offsets.field_count,
+        constants::TYPE_DEFINITION_FIELD_COUNT as usize
+    );
+    assert!(offsets
+        .profile()
+        .contains("TYPE_DEFINITION_FIELDS: u32 = 0x98;"));
+
+    let missing = vec!["title".to_string(), "grpId".to_string()];
+    assert!(calibrate::find_field_offsets(&mono_reader, class.address(), &missing).is_none());
+}
+
+#[test]
+fn test_read_interface_field() {
+    let interface = fake_mono::FakeClass::new("IInventoryService", &[]).in_namespace("Wotc");
+    let service =
+        fake_mono::FakeClass::new("InventoryService", &[("gems", TypeCode::I4 as u8, 0x10)])
+            .in_namespace("Wotc");
+    let holder = fake_mono::FakeClass::new(
+        "Holder",
+        &[
+            ("service", TypeCode::CLASS as u8, 0x10),
+            ("same", TypeCode::CLASS as u8, 0x18),
+        ],
+    )
+    .field_type_data(0, interface.address())
+    .field_type_data(1, service.address());
+
+    let service_object: [usize; 3] = [service.vtable(), 0, 1500];
+    let ptr = service_object.as_ptr() as usize;
+    let holder_object: [usize; 4] = [holder.vtable(), 0, ptr, ptr];
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let mut class = TypeDefinition::new(holder.address(), &mono_reader);
+    class.set_fields_base(holder_object.as_ptr() as usize);
+    class.set_depth(1);
+
+    assert_eq!(
+        to_json(class.to_string()),
+        json!({
+            "service": {
+                "type": "object",
+                "declared_type": "Wotc.IInventoryService",
+                "runtime_type": "Wotc.InventoryService",
+                "value": { "gems": 1500 }
+            },
+            "same": { "gems": 1500 }
+        })
+    );
+}
+
+#[test]
+fn test_parse_unity_version() {
+    let version = unity_version::UnityVersion::parse("2021.3.14f1").unwrap();
+    assert_eq!(version.major, 2021);
+    assert_eq!(version.minor, 3);
+    assert_eq!(version.patch, 14);
+    assert_eq!(version.release_type, 'f');
+    assert_eq!(version.build, 1);
+    assert_eq!(version.to_string(), "2021.3.14f1");
+
+    assert_eq!(unity_version::UnityVersion::parse("5.x.x"), None);
+    assert_eq!(unity_version::UnityVersion::parse("2021.3"), None);
+}
+
+#[test]
+fn test_find_unity_version_in_header() {
+    // UnityFS bundle header: signature, format, player version, engine version
+    let mut header = b"UnityFS\0\0\0\0\x065.x.x\0".to_vec();
+    header.extend_from_slice(b"2021.3.14f1\0\0\0\x01\x02");
+
+    let version = unity_version::find_version_in_header(&header).unwrap();
+    assert_eq!(version.to_string(), "2021.3.14f1");
+
+    assert_eq!(
+        unity_version::find_version_in_header(b"\0\0UnityFS\0"),
+        None
+    );
+}
+
+#[test]
+fn test_metadata_constant_values() {
+    let root = fake_mono::fake_metadata_root([1, 3]);
+    let metadata = metadata::Metadata::from_metadata_root(root, 0).unwrap();
+
+    assert_eq!(
+        metadata.constant_values(0x02000001),
+        vec![("Red".to_string(), json!(-2))]
+    );
+    assert_eq!(
+        metadata.constant_values(0x02000002),
+        vec![("Name".to_string(), json!("hi"))]
+    );
+    assert!(metadata.constant_values(0x02000003).is_empty());
+    assert!(metadata.constant_values(0x01000001).is_empty());
+
+    assert_eq!(metadata.field_count(0x02000001), Some(2));
+    assert_eq!(metadata.field_count(0x02000002), Some(1));
+    assert_eq!(metadata.field_count(0x02000003), None);
+
+    // a 0 FieldList is a misread, not the field before the first one
+    let root = fake_mono::fake_metadata_root([0, 3]);
+    let metadata = metadata::Metadata::from_metadata_root(root, 0).unwrap();
+    assert!(metadata.constant_values(0x02000001).is_empty());
+    assert_eq!(metadata.field_count(0x02000001), None);
+    assert_eq!(metadata.field_count(0x02000002), Some(1));
+}
+
+#[test]
+fn test_check_field_count() {
+    let raw_data = fake_mono::fake_pe_image(&fake_mono::fake_metadata_root([1, 3]));
+    let image = fake_mono::fake_image(&raw_data);
+
+    // Color has Red and Blue in the metadata, the runtime class lost one
+    let color = fake_mono::FakeClass::new("Color", &[("Red", TypeCode::I4 as u8, 0)])
+        .in_image(image.as_ptr() as usize, 0x02000001);
+    let other = fake_mono::FakeClass::new("Other", &[("Name", TypeCode::STRING as u8, 0)])
+        .in_image(image.as_ptr() as usize, 0x02000002);
+    let unloaded = fake_mono::FakeClass::new("Unloaded", &[]);
+
+    let mono_reader = MonoReader::new(std::process::id());
+    let check = |class: &fake_mono::FakeClass| {
+        TypeDefinition::new(class.address(), &mono_reader).check_field_count()
+    };
+
+    assert_eq!(check(&color), Some((1, 2)));
+    assert_eq!(check(&other), Some((1, 1)));
+    assert_eq!(check(&unloaded), None);
 }
 
 /*
@@ -4,6 +4,122 @@ use crate::type_definition::TypeDefinition;
 use crate::type_info::TypeInfo;
 use crate::{constants, MonoReader};
 use std::cmp;
+use std::collections::HashSet;
+
+// A string field, keeping apart a null reference, an empty string and a
+// string we failed to read
+pub enum ManagedString {
+    Null,
+    Empty,
+    Value(String),
+    Unreadable,
+}
+
+impl ManagedString {
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            ManagedString::Null => serde_json::Value::Null,
+            ManagedString::Empty => serde_json::Value::String(String::new()),
+            ManagedString::Value(string) => serde_json::Value::String(string.clone()),
+            ManagedString::Unreadable => serde_json::json!({ "type": "unreadable" }),
+        }
+    }
+}
+
+// Field names of Dictionary<TKey, TValue>, they differ between the class
+// libraries a Unity version ships with
+pub struct DictionaryLayout {
+    pub name: &'static str,
+    pub entries: &'static str,
+    pub count: &'static str,
+    pub free_list: &'static str,
+    pub free_count: &'static str,
+    pub comparer: &'static str,
+}
+
+// Where the entries of a Dictionary are and which of them are in use
+struct DictionaryHeader {
+    entries_location: usize,
+    count: u32,
+    // head of the chain of removed entries and its length, -1 and 0 when
+    // nothing was removed
+    free_list: i32,
+    free_count: u32,
+}
+
+// Removed entries of a corefx Dictionary store their free list link as
+// StartOfFreeList - next, older ones store next as is
+const DICTIONARY_START_OF_FREE_LIST: i32 = -3;
+
+pub const DICTIONARY_LAYOUTS: [DictionaryLayout; 2] = [
+    // corefx based class libraries, what MTGA (Unity 2021.3) ships with
+    DictionaryLayout {
+        name: "modern",
+        entries: "_entries",
+        count: "_count",
+        free_list: "_freeList",
+        free_count: "_freeCount",
+        comparer: "_comparer",
+    },
+    // referencesource based mscorlib of older Unity versions
+    DictionaryLayout {
+        name: "legacy",
+        entries: "entries",
+        count: "count",
+        free_list: "freeList",
+        free_count: "freeCount",
+        comparer: "comparer",
+    },
+];
+
+// Field names of ConcurrentDictionary<TKey, TValue>, of the Tables holding
+// its buckets and of the Node chained in every bucket
+pub struct ConcurrentDictionaryLayout {
+    pub tables: &'static str,
+    pub buckets: &'static str,
+    pub key: &'static str,
+    pub value: &'static str,
+    pub next: &'static str,
+    // int[] of the tables, the entries held under each lock
+    pub count_per_lock: &'static str,
+}
+
+pub const CONCURRENT_DICTIONARY_LAYOUTS: [ConcurrentDictionaryLayout; 2] = [
+    // corefx
+    ConcurrentDictionaryLayout {
+        tables: "_tables",
+        buckets: "_buckets",
+        key: "_key",
+        value: "_value",
+        next: "_next",
+        count_per_lock: "_countPerLock",
+    },
+    // referencesource
+    ConcurrentDictionaryLayout {
+        tables: "m_tables",
+        buckets: "m_buckets",
+        key: "m_key",
+        value: "m_value",
+        next: "m_next",
+        count_per_lock: "m_countPerLock",
+    },
+];
+
+// Unity structs of floats, read straight from their inline values with the
+// names of their components
+const UNITY_FLOAT_STRUCTS: [(&str, &[&str]); 4] = [
+    ("UnityEngine.Color", &["r", "g", "b", "a"]),
+    ("UnityEngine.Vector2", &["x", "y"]),
+    ("UnityEngine.Vector3", &["x", "y", "z"]),
+    ("UnityEngine.Vector4", &["x", "y", "z", "w"]),
+];
+
+// Nodes read at most from a ConcurrentDictionary, a chain read while the
+// game rewrites it could loop
+const MAX_CONCURRENT_DICTIONARY_NODES: usize = 1 << 20;
+
+// (pairs read, count of the whole dictionary) of a ConcurrentDictionary
+type ConcurrentDictionaryRead = (Vec<(serde_json::Value, serde_json::Value)>, Option<u32>);
 
 pub struct Managed<'a> {
     reader: &'a MonoReader,
@@ -37,12 +153,12 @@ impl<'a> Managed<'a> {
         self.reader.read_u32(self.addr)
     }
 
-    pub fn read_r4(&self) -> i32 {
-        self.reader.read_i32(self.addr)
+    pub fn read_r4(&self) -> f32 {
+        f32::from_bits(self.reader.read_u32(self.addr))
     }
 
-    pub fn read_r8(&self) -> i64 {
-        self.reader.read_i64(self.addr)
+    pub fn read_r8(&self) -> f64 {
+        f64::from_bits(self.reader.read_u64(self.addr))
     }
 
     // read_i
@@ -50,6 +166,14 @@ impl<'a> Managed<'a> {
         self.reader.read_i32(self.addr)
     }
 
+    pub fn read_i8(&self) -> i64 {
+        self.reader.read_i64(self.addr)
+    }
+
+    pub fn read_u8(&self) -> u64 {
+        self.reader.read_u64(self.addr)
+    }
+
     pub fn read_i2(&self) -> i16 {
         self.reader.read_i16(self.addr)
     }
@@ -59,27 +183,231 @@ impl<'a> Managed<'a> {
     }
 
     pub fn read_string(&self) -> String {
-        let ptr = self.reader.read_ptr(self.addr);
-        let length = self
+        match self.read_managed_string() {
+            ManagedString::Value(string) => string,
+            _ => String::new(),
+        }
+    }
+
+    pub fn read_managed_string(&self) -> ManagedString {
+        let ptr = match self.reader.maybe_read_ptr(self.addr) {
+            Some(ptr) => ptr,
+            None => return ManagedString::Unreadable,
+        };
+
+        if ptr == 0 {
+            return ManagedString::Null;
+        }
+
+        let length = match self.reader.maybe_read_u32(ptr + constants::STRING_LENGTH) {
+            Some(length) => length,
+            None => return ManagedString::Unreadable,
+        };
+
+        if length == 0 {
+            return ManagedString::Empty;
+        }
+
+        let cap_length = cmp::min(length as usize, 1024);
+
+        let bytes = match self
             .reader
-            .read_u32(ptr + (constants::SIZE_OF_PTR * 2 as usize));
+            .read_bytes(ptr + constants::STRING_CHARS, cap_length * 2)
+        {
+            Some(bytes) => bytes,
+            None => return ManagedString::Unreadable,
+        };
 
-        let mut str = Vec::new();
+        // The chars are UTF-16 code units
+        let str = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<u16>>();
 
-        let cap_length = cmp::min(length as u64 * 2, 1024);
+        return ManagedString::Value(String::from_utf16_lossy(&str));
+    }
 
-        for i in 0..(cap_length) {
-            let val = self
-                .reader
-                .read_u16(ptr + (constants::SIZE_OF_PTR * 2 as usize) + 4 + (i as usize));
+    // Reads the value stored inline at self.addr as a JSON fragment
+    pub fn read_value(&self, type_info: TypeInfo) -> String {
+        match type_info.clone().code() {
+            TypeCode::BOOLEAN => self.read_boolean().to_string(),
+            TypeCode::U4 => self.read_u4().to_string(),
+            TypeCode::U => self.read_u4().to_string(),
+            TypeCode::R4 => float_to_json(self.read_r4()),
+            TypeCode::R8 => float_to_json(self.read_r8()),
+            TypeCode::I4 => self.read_i4().to_string(),
+            TypeCode::I => self.read_i4().to_string(),
+            TypeCode::I2 => self.read_i2().to_string(),
+            TypeCode::U2 => self.read_u2().to_string(),
+            TypeCode::I8 => int64_to_json(self.read_i8()),
+            TypeCode::U8 => uint64_to_json(self.read_u8()),
+            TypeCode::STRING => self.read_managed_string().to_json().to_string(),
+            TypeCode::VALUETYPE => self.read_struct(&type_info),
+            TypeCode::OBJECT => self.read_object(),
+            _ => "null".to_string(),
+        }
+    }
+
+    // Reads an object/interface field. Value types stored in one are boxed,
+    // their value follows the object header of the box.
+    pub fn read_object(&self) -> String {
+        // read_ptr counts an unreadable field, strict reads mustn't take
+        // its null for a real one
+        let ptr = self.reader.read_ptr(self.addr);
+        if ptr == 0 {
+            return String::from("null");
+        }
+
+        let class = self.read_class();
+        match class.type_info.clone().code() {
+            TypeCode::STRING => return self.read_managed_string().to_json().to_string(),
+            TypeCode::SZARRAY | TypeCode::ARRAY => return self.read_managed_array(),
+            _ => {}
+        }
+        if !class.is_value_type {
+            return String::from("null");
+        }
+
+        let payload = Managed::new(self.reader, ptr + constants::OBJECT_HEADER_SIZE, None);
+        let value = payload.read_value(class.type_info.clone());
+
+        format!(
+            "{{ \"type\": \"boxed\", \"value_type\": {}, \"value\": {} }}",
+            serde_json::Value::String(class.full_name()),
+            value
+        )
+    }
+
+    // Reads the struct stored inline at self.addr, the data of a VALUETYPE
+    // type points to the struct class which gives us its field layout
+    pub fn read_struct(&self, type_info: &TypeInfo) -> String {
+        let mut class = match type_info.resolve_class(self.reader) {
+            Some(class) => class,
+            None => return self.read_valuetype().to_string(),
+        };
+        if class.is_enum {
+            return self.read_enum(&class);
+        }
+        if !class.is_value_type {
+            return self.read_valuetype().to_string();
+        }
+
+        if class.full_name() == "System.Decimal" {
+            return self.read_decimal();
+        }
+
+        if let Some((_, components)) = UNITY_FLOAT_STRUCTS
+            .iter()
+            .find(|(name, _)| *name == class.full_name())
+        {
+            return self.read_float_struct(components);
+        }
+
+        class.set_fields_base(self.addr);
+        class.to_string()
+    }
+
+    // Reads the enum stored inline at self.addr at the width and sign of its
+    // underlying type, as an int when that can't be read
+    pub fn read_enum(&self, class: &TypeDefinition) -> String {
+        let code = class
+            .enum_underlying_type()
+            .map(|type_info| type_info.code())
+            .unwrap_or(TypeCode::I4);
+
+        match code {
+            TypeCode::I1 => self.reader.read_i8(self.addr).to_string(),
+            TypeCode::U1 => self.reader.read_u8(self.addr).to_string(),
+            TypeCode::I2 => self.read_i2().to_string(),
+            TypeCode::U2 | TypeCode::CHAR => self.read_u2().to_string(),
+            TypeCode::U4 => self.read_u4().to_string(),
+            TypeCode::I8 => int64_to_json(self.read_i8()),
+            TypeCode::U8 => uint64_to_json(self.read_u8()),
+            _ => self.read_valuetype().to_string(),
+        }
+    }
+
+    // Reads the floats stored inline at self.addr as an object with the
+    // given component names, in order
+    pub fn read_float_struct(&self, components: &[&str]) -> String {
+        let bytes = match self.reader.read_bytes(self.addr, components.len() * 4) {
+            Some(bytes) => bytes,
+            None => return String::from("null"),
+        };
+
+        let values: Vec<String> = components
+            .iter()
+            .zip(bytes.chunks_exact(4))
+            .map(|(name, value)| {
+                let value = f32::from_le_bytes(value.try_into().unwrap());
+                format!("\"{}\": {}", name, float_to_json(value))
+            })
+            .collect();
+
+        format!("{{ {} }}", values.join(", "))
+    }
 
-            str.push(val);
+    // System.Decimal: flags (sign and scale), then the 96 bit integer as
+    // hi, lo, mid. The value is emitted as a string to keep every digit.
+    pub fn read_decimal(&self) -> String {
+        let bytes = match self.reader.read_bytes(self.addr, 16) {
+            Some(bytes) => bytes,
+            None => return String::from("null"),
+        };
+        let part =
+            |index: usize| u32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap());
+
+        format!(
+            "{{ \"type\": \"decimal\", \"value\": \"{}\" }}",
+            format_decimal(part(0), part(1), part(2), part(3))
+        )
+    }
+
+    // Reads the reference field at self.addr by the class of the object it
+    // points to, the declared type can be object, an interface or a base
+    // class of a string, an array or a boxed value
+    pub fn read_instance_field(&self, declared_type: &TypeInfo, depth: u32) -> String {
+        let ptr = self.reader.read_ptr(self.addr);
+        if ptr == 0 {
+            return String::from("null");
+        }
+
+        let class = self.read_class();
+        let by_value = matches!(
+            class.type_info.clone().code(),
+            TypeCode::STRING | TypeCode::SZARRAY | TypeCode::ARRAY
+        );
+        if by_value || class.is_value_type {
+            return self.read_object();
+        }
+
+        self.read_referenced_object(declared_type, depth)
+    }
+
+    // Reads the object the reference at self.addr points to, expanding depth
+    // levels of nested objects. The layout comes from the object's own
+    // class, which for a field typed as an interface or a base class is not
+    // the declared one, the runtime type is reported next to it then.
+    pub fn read_referenced_object(&self, declared_type: &TypeInfo, depth: u32) -> String {
+        let ptr = self.reader.read_ptr(self.addr);
+        if ptr == 0 {
+            return String::from("null");
         }
 
-        // Convert the vector to a string
-        let string: String = str.iter().map(|&c| c as u8 as char).collect::<String>();
+        let mut class = self.read_class();
+        class.set_fields_base(ptr);
+        class.set_depth(depth);
+        let value = class.to_string();
 
-        return string;
+        match declared_type.resolve_class(self.reader) {
+            Some(declared) if declared.full_name() != class.full_name() => format!(
+                "{{ \"type\": \"object\", \"declared_type\": {}, \"runtime_type\": {}, \"value\": {} }}",
+                serde_json::Value::String(declared.full_name()),
+                serde_json::Value::String(class.full_name()),
+                value
+            ),
+            _ => value,
+        }
     }
 
     pub fn read_valuetype(&self) -> i32 {
@@ -123,6 +451,678 @@ impl<'a> Managed<'a> {
     // pub fn read_managed_array<T>(&self) -> Option<T>
 
     pub fn read_managed_array(&self) -> String {
+        self.read_managed_array_elements(u32::MAX, 1)
+    }
+
+    // Reads the List<T> pointed by self.addr, only its first _size elements
+    // are in use. depth is how many levels of objects are expanded, with 0
+    // class elements are emitted as their address.
+    pub fn read_managed_list(&self, depth: u32) -> String {
+        let ptr = self.reader.read_ptr(self.addr);
+        if ptr == 0 {
+            return String::from("null");
+        }
+
+        let class = self.read_class();
+        if class.get_field("_items").0 == 0 || class.get_field("_size").0 == 0 {
+            return String::from("null");
+        }
+
+        let size = self.reader.read_u32(class.get_value("_size", ptr).0);
+        let items = Managed::new(self.reader, class.get_value("_items", ptr).0, None);
+
+        items.read_managed_array_elements(size, depth)
+    }
+
+    // The first limit entries of the List, Dictionary or ConcurrentDictionary
+    // pointed by self.addr, as { "count": n, "entries": [...] } with count
+    // the size of the whole collection, null when it can't be known without
+    // reading every entry. None for any other object.
+    pub fn read_collection_preview(&self, limit: u32) -> Option<String> {
+        let ptr = self.reader.maybe_read_ptr(self.addr)?;
+        if ptr == 0 {
+            return None;
+        }
+
+        let class = self.read_class();
+        if class.get_field("_items").0 != 0 && class.get_field("_size").0 != 0 {
+            let size = self
+                .reader
+                .maybe_read_u32(class.get_value("_size", ptr).0)?;
+            let items = Managed::new(self.reader, class.get_value("_items", ptr).0, None);
+
+            return Some(format!(
+                "{{ \"count\": {}, \"entries\": {} }}",
+                size,
+                items.read_managed_array_elements(size.min(limit), 1)
+            ));
+        }
+
+        let (count, pairs) = match self.read_dict_header(None) {
+            // the count of the header includes the removed entries
+            Some(header) => {
+                let mut pairs = Vec::new();
+                self.read_dict_batches_while(None, limit.max(1), |batch| {
+                    pairs.extend(batch);
+                    pairs.len() < limit as usize
+                })?;
+
+                (
+                    Some(header.count - header.free_count.min(header.count)),
+                    pairs,
+                )
+            }
+            None => {
+                let (pairs, count) = self.read_concurrent_dict_first(limit as usize)?;
+                // without the lock counts only a read that ended before the
+                // limit saw the whole dictionary
+                let count = match count {
+                    None if pairs.len() < limit as usize => Some(pairs.len() as u32),
+                    count => count,
+                };
+                (count, pairs)
+            }
+        };
+
+        let entries: Vec<serde_json::Value> = pairs
+            .into_iter()
+            .take(limit as usize)
+            .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+            .collect();
+
+        Some(format!(
+            "{{ \"count\": {}, \"entries\": {} }}",
+            serde_json::json!(count),
+            serde_json::Value::Array(entries)
+        ))
+    }
+
+    // Finds the _entries location, _count and free list of the Dictionary
+    // pointed by self.addr, with the given field names or the first layout
+    // that gives a count that fits in the entries array
+    fn read_dict_header(&self, layout: Option<&DictionaryLayout>) -> Option<DictionaryHeader> {
+        let ptr = self.reader.maybe_read_ptr(self.addr)?;
+        if ptr == 0 {
+            return None;
+        }
+
+        let class = self.read_class();
+        let layouts = match layout {
+            Some(layout) => std::slice::from_ref(layout),
+            None => &DICTIONARY_LAYOUTS[..],
+        };
+
+        layouts.iter().find_map(|layout| {
+            if class.get_field(layout.entries).0 == 0 || class.get_field(layout.count).0 == 0 {
+                return None;
+            }
+
+            let entries_location = class.get_value(layout.entries, ptr).0;
+            let count = self
+                .reader
+                .maybe_read_u32(class.get_value(layout.count, ptr).0)?;
+
+            let entries = self.reader.maybe_read_ptr(entries_location)?;
+            let length = match entries {
+                0 => 0,
+                _ => self.reader.read_array_length(entries)?,
+            };
+
+            if count > length {
+                return None;
+            }
+
+            // without the free list fields every entry of _count is used
+            let (free_list, free_count) = match class.get_field(layout.free_count).0 {
+                0 => (-1, 0),
+                _ => (
+                    self.reader
+                        .maybe_read_u32(class.get_value(layout.free_list, ptr).0)
+                        .map_or(-1, |free_list| free_list as i32),
+                    self.reader
+                        .maybe_read_u32(class.get_value(layout.free_count, ptr).0)
+                        .unwrap_or(0),
+                ),
+            };
+
+            Some(DictionaryHeader {
+                entries_location,
+                count,
+                free_list,
+                free_count,
+            })
+        })
+    }
+
+    // Indexes of the removed entries, following the free list from its head
+    // through the next field of each entry. Those entries are left in place
+    // until reused, some class libraries keep their hash code.
+    fn read_free_entries(&self, header: &DictionaryHeader, stride: usize) -> HashSet<u32> {
+        let mut free = HashSet::new();
+        let entries = self
+            .reader
+            .maybe_read_ptr(header.entries_location)
+            .unwrap_or(0);
+        if entries == 0 || stride < 8 {
+            return free;
+        }
+
+        let mut index = header.free_list;
+        for _ in 0..header.free_count.min(header.count) {
+            if index < 0 || index as u32 >= header.count || !free.insert(index as u32) {
+                break;
+            }
+
+            let next = self
+                .reader
+                .maybe_read_u32(
+                    entries + constants::ARRAY_HEADER_SIZE + index as usize * stride + 4,
+                )
+                .map_or(-1, |next| next as i32);
+            index = match next {
+                next if next < -1 => DICTIONARY_START_OF_FREE_LIST - next,
+                next => next,
+            };
+        }
+
+        free
+    }
+
+    // Key type of the Dictionary pointed by self.addr, from the class of its
+    // comparer: GenericEqualityComparer<uint> and the like have the key type
+    // as type argument, the comparers of string keys aren't generic. None
+    // without a comparer, the default one isn't always stored.
+    pub fn read_dict_key_type(&self) -> Option<TypeInfo> {
+        let ptr = self.reader.maybe_read_ptr(self.addr)?;
+        if ptr == 0 {
+            return None;
+        }
+
+        let class = self.read_class();
+        let layout = DICTIONARY_LAYOUTS
+            .iter()
+            .find(|layout| class.get_field(layout.comparer).0 != 0)?;
+
+        let comparer_location = class.get_value(layout.comparer, ptr).0;
+        if self.reader.maybe_read_ptr(comparer_location)? == 0 {
+            return None;
+        }
+
+        let comparer = Managed::new(self.reader, comparer_location, None);
+        let comparer = comparer.read_class();
+        if let Some(key_type) = comparer.generic_type_args.first() {
+            return Some(key_type.clone());
+        }
+
+        if comparer.name.contains("String") || comparer.name.contains("Ordinal") {
+            return Some(TypeInfo::from_code(TypeCode::STRING as u32));
+        }
+
+        None
+    }
+
+    // Reads the (key, value) pairs of the Dictionary pointed by self.addr,
+    // for 4 byte keys and values like Dictionary<uint, int>. Slots freed by
+    // Remove are on the free list or keep a negative hash code, and are
+    // skipped. The layout is detected when None.
+    pub fn read_dict_entries(&self, layout: Option<&DictionaryLayout>) -> Option<Vec<(u32, i32)>> {
+        let header = self.read_dict_header(layout)?;
+        let (entries_location, count) = (header.entries_location, header.count);
+
+        let entries = self.reader.maybe_read_ptr(entries_location)?;
+        if entries == 0 {
+            return Some(Vec::new());
+        }
+
+        let stride = self.entry_stride(entries_location, constants::DICTIONARY_ENTRY_SIZE);
+        let bytes = self.reader.read_bytes(
+            entries + constants::ARRAY_HEADER_SIZE,
+            count as usize * stride,
+        )?;
+
+        let read_i32 = |entry: &[u8], offset: usize| {
+            entry
+                .get(offset..offset + 4)
+                .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+
+        // malformed entries are skipped, a bad layout mustn't take the game
+        // reader down with it
+        let free = self.read_free_entries(&header, stride);
+        let pairs = bytes
+            .chunks_exact(stride)
+            .enumerate()
+            .filter(|(index, _)| !free.contains(&(*index as u32)))
+            .filter_map(|(_, entry)| {
+                let hash_code = read_i32(entry, 0)?;
+                let key = read_i32(entry, 8)?;
+                let value = read_i32(entry, 12)?;
+                (hash_code >= 0).then_some((key as u32, value))
+            })
+            .collect();
+
+        Some(pairs)
+    }
+
+    // Reads the (key, value) pairs of the Dictionary pointed by self.addr,
+    // for any key and value types. None if the object isn't a Dictionary.
+    pub fn read_dict_pairs(
+        &self,
+        layout: Option<&DictionaryLayout>,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        if let Some(pairs) = self.read_concurrent_dict_pairs() {
+            return Some(pairs);
+        }
+
+        let mut pairs = Vec::new();
+        self.read_dict_batches(layout, u32::MAX, |batch| pairs.extend(batch))?;
+
+        Some(pairs)
+    }
+
+    // Same as read_dict_pairs, values that are dictionaries too are read as
+    // their pairs instead of their fields, down to depth levels. budget is
+    // the number of nested entries still allowed, shared by all the levels
+    // so a dictionary of big dictionaries can't read without end.
+    pub fn read_dict_nested(
+        &self,
+        depth: u32,
+        budget: &mut u32,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        let mut pairs = self.read_dict_pairs(None)?;
+        if depth == 0 || pairs.is_empty() {
+            return Some(pairs);
+        }
+
+        // a ConcurrentDictionary has no entries array, its values are left
+        // as they were read
+        let header = match self.read_dict_header(None) {
+            Some(header) => header,
+            None => return Some(pairs),
+        };
+        let entries = self.reader.maybe_read_ptr(header.entries_location)?;
+        // element class of the Entry[] array
+        let entries_class =
+            Managed::new(self.reader, header.entries_location, None).read_class_address();
+        let entry_class = TypeDefinition::new(self.reader.read_ptr(entries_class), self.reader);
+        let stride = entry_class.value_size();
+        if stride == 0 || entry_class.get_field("value").0 == 0 {
+            return Some(pairs);
+        }
+
+        // the live entries, in the order read_dict_pairs returns them
+        let free = self.read_free_entries(&header, stride);
+        let live_entries = (0..header.count)
+            .filter(|index| !free.contains(index))
+            .map(|index| entries + constants::ARRAY_HEADER_SIZE + index as usize * stride)
+            .filter(|entry| self.reader.maybe_read_u32(*entry).unwrap_or(u32::MAX) as i32 >= 0);
+
+        for ((_, value), entry) in pairs.iter_mut().zip(live_entries) {
+            let (location, type_info) = entry_class.get_value("value", entry);
+            if !matches!(type_info.code(), TypeCode::CLASS | TypeCode::GENERICINST) {
+                continue;
+            }
+
+            if let Some(inner) =
+                Managed::new(self.reader, location, None).read_dict_nested_within(depth - 1, budget)
+            {
+                *value = serde_json::Value::Array(
+                    inner
+                        .into_iter()
+                        .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+                        .collect(),
+                );
+            }
+        }
+
+        Some(pairs)
+    }
+
+    // read_dict_nested of an inner dictionary, None once over budget
+    fn read_dict_nested_within(
+        &self,
+        depth: u32,
+        budget: &mut u32,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        let count = self.read_dict_header(None)?.count;
+        if count > *budget {
+            eprintln!(
+                "Nested dictionary entry budget used up, leaving {} entries unread",
+                count
+            );
+            return None;
+        }
+        *budget -= count;
+
+        self.read_dict_nested(depth, budget)
+    }
+
+    // Reads the (key, value) pairs of the ConcurrentDictionary pointed by
+    // self.addr. Its entries aren't in one array like a Dictionary's, every
+    // bucket of its tables is a linked list of nodes. None if the object
+    // isn't a ConcurrentDictionary.
+    pub fn read_concurrent_dict_pairs(
+        &self,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        let (pairs, count) = self.read_concurrent_dict_first(MAX_CONCURRENT_DICTIONARY_NODES)?;
+        if pairs.len() >= MAX_CONCURRENT_DICTIONARY_NODES && count != Some(pairs.len() as u32) {
+            eprintln!(
+                "ConcurrentDictionary has over {} nodes, reading stopped",
+                MAX_CONCURRENT_DICTIONARY_NODES
+            );
+        }
+
+        Some(pairs)
+    }
+
+    // The first limit pairs of the ConcurrentDictionary pointed by self.addr,
+    // with its count summed from the count of each lock when it can be read
+    fn read_concurrent_dict_first(&self, limit: usize) -> Option<ConcurrentDictionaryRead> {
+        let ptr = self.reader.maybe_read_ptr(self.addr)?;
+        if ptr == 0 {
+            return None;
+        }
+
+        let class = self.read_class();
+        if !class.name.starts_with("ConcurrentDictionary") {
+            return None;
+        }
+        let layout = CONCURRENT_DICTIONARY_LAYOUTS
+            .iter()
+            .find(|layout| class.get_field(layout.tables).0 != 0)?;
+
+        let tables_location = class.get_value(layout.tables, ptr).0;
+        let tables = self.reader.maybe_read_ptr(tables_location)?;
+        if tables == 0 {
+            return Some((Vec::new(), Some(0)));
+        }
+        let tables_object = Managed::new(self.reader, tables_location, None);
+        let tables_class = tables_object.read_class();
+        let count = match tables_class.get_field(layout.count_per_lock).0 {
+            0 => None,
+            _ => self.read_int_array_sum(tables_class.get_value(layout.count_per_lock, tables).0),
+        };
+        let buckets = self
+            .reader
+            .maybe_read_ptr(tables_class.get_value(layout.buckets, tables).0)?;
+        if buckets == 0 {
+            return Some((Vec::new(), count));
+        }
+        let bucket_count = self.reader.read_array_length(buckets)? as usize;
+
+        let mut pairs = Vec::new();
+        for bucket in 0..bucket_count {
+            let mut node_location =
+                buckets + constants::ARRAY_HEADER_SIZE + bucket * constants::SIZE_OF_PTR;
+
+            loop {
+                let node = self.reader.maybe_read_ptr(node_location).unwrap_or(0);
+                if node == 0 {
+                    break;
+                }
+                if pairs.len() >= limit {
+                    return Some((pairs, count));
+                }
+
+                let node_object = Managed::new(self.reader, node_location, None);
+                let node_class = node_object.read_class();
+                let (key_location, key_type) = node_class.get_value(layout.key, node);
+                let (value_location, value_type) = node_class.get_value(layout.value, node);
+                pairs.push((
+                    self.read_json_at(key_location, key_type),
+                    self.read_json_at(value_location, value_type),
+                ));
+
+                node_location = node_class.get_value(layout.next, node).0;
+            }
+        }
+
+        Some((pairs, count))
+    }
+
+    // Sum of the int[] pointed by location, None if it can't be read
+    fn read_int_array_sum(&self, location: usize) -> Option<u32> {
+        let array = self.reader.maybe_read_ptr(location)?;
+        if array == 0 {
+            return None;
+        }
+
+        let length = self.reader.read_array_length(array)? as usize;
+        let bytes = self
+            .reader
+            .read_bytes(array + constants::ARRAY_HEADER_SIZE, length * 4)?;
+
+        Some(
+            bytes
+                .chunks_exact(4)
+                .map(|value| i32::from_le_bytes(value.try_into().unwrap()).max(0) as u32)
+                .sum(),
+        )
+    }
+
+    // The value of the given type stored at location, objects have their
+    // own fields read but not the ones of the objects they reference
+    fn read_json_at(&self, location: usize, type_info: TypeInfo) -> serde_json::Value {
+        let managed = Managed::new(self.reader, location, None);
+        let value = match type_info.clone().code() {
+            TypeCode::CLASS | TypeCode::GENERICINST | TypeCode::OBJECT | TypeCode::SZARRAY => {
+                managed.read_instance_field(&type_info, 0)
+            }
+            _ => managed.read_value(type_info),
+        };
+
+        serde_json::from_str(&value).unwrap_or(serde_json::Value::Null)
+    }
+
+    // Same as read_dict_pairs, handing the pairs to on_batch batch_size
+    // entries at a time instead of collecting them all
+    pub fn read_dict_batches(
+        &self,
+        layout: Option<&DictionaryLayout>,
+        batch_size: u32,
+        mut on_batch: impl FnMut(Vec<(serde_json::Value, serde_json::Value)>),
+    ) -> Option<()> {
+        self.read_dict_batches_while(layout, batch_size, |batch| {
+            on_batch(batch);
+            true
+        })
+    }
+
+    // read_dict_batches stopping as soon as on_batch returns false
+    fn read_dict_batches_while(
+        &self,
+        layout: Option<&DictionaryLayout>,
+        batch_size: u32,
+        mut on_batch: impl FnMut(Vec<(serde_json::Value, serde_json::Value)>) -> bool,
+    ) -> Option<()> {
+        let header = self.read_dict_header(layout)?;
+        let count = header.count;
+        let entries = Managed::new(self.reader, header.entries_location, None);
+        let free =
+            self.read_free_entries(&header, self.array_element_size(header.entries_location));
+
+        let mut first = 0;
+        while first < count {
+            let limit = batch_size.min(count - first);
+            let batch: serde_json::Value =
+                serde_json::from_str(&entries.read_managed_array_range(first, limit, 1)).ok()?;
+
+            // removed entries are on the free list or keep a negative hash
+            // code
+            let more = on_batch(
+                batch
+                    .as_array()?
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, entry)| {
+                        entry["hashCode"].as_i64().unwrap_or(-1) >= 0
+                            && !free.contains(&(first + *index as u32))
+                    })
+                    .map(|(_, entry)| (entry["key"].clone(), entry["value"].clone()))
+                    .collect(),
+            );
+            if !more {
+                break;
+            }
+
+            first += limit;
+        }
+
+        Some(())
+    }
+
+    // Reads the Dictionary pointed by self.addr as Dictionary<key, value>
+    // with the given types instead of the ones of its class. The entry is
+    // laid out as { int hashCode; int next; key; value; } with natural
+    // alignment.
+    pub fn read_dict_typed(
+        &self,
+        key_type: &TypeInfo,
+        value_type: &TypeInfo,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        self.read_dict_typed_expanded(key_type, value_type, 0)
+    }
+
+    // Same as read_dict_typed, reference values are read depth levels deep
+    // instead of as their address: dictionaries as their pairs through
+    // read_dict_nested, other objects as their fields. The nested entries
+    // share one budget so a dictionary of big dictionaries stays bounded.
+    pub fn read_dict_typed_expanded(
+        &self,
+        key_type: &TypeInfo,
+        value_type: &TypeInfo,
+        depth: u32,
+    ) -> Option<Vec<(serde_json::Value, serde_json::Value)>> {
+        let (key_offset, value_offset, stride) =
+            dictionary_entry_layout(key_type.clone().code(), value_type.clone().code())?;
+
+        let header = self.read_dict_header(None)?;
+        let (entries_location, count) = (header.entries_location, header.count);
+        let entries = self.reader.maybe_read_ptr(entries_location)?;
+        if entries == 0 {
+            return Some(Vec::new());
+        }
+
+        let mut budget = constants::MAX_NESTED_DICTIONARY_ENTRIES;
+        let mut read = |addr: usize, type_info: &TypeInfo| {
+            let managed = Managed::new(self.reader, addr, None);
+            let value = match type_info.clone().code() {
+                TypeCode::CLASS | TypeCode::GENERICINST if depth > 0 => {
+                    match managed.read_dict_nested_within(depth - 1, &mut budget) {
+                        Some(pairs) => {
+                            return serde_json::Value::Array(
+                                pairs
+                                    .into_iter()
+                                    .map(|(key, value)| {
+                                        serde_json::json!({ "key": key, "value": value })
+                                    })
+                                    .collect(),
+                            )
+                        }
+                        None => managed.read_instance_field(type_info, depth - 1),
+                    }
+                }
+                // references are returned as their address
+                TypeCode::CLASS | TypeCode::SZARRAY | TypeCode::GENERICINST => {
+                    self.reader.read_ptr(addr).to_string()
+                }
+                _ => managed.read_value(type_info.clone()),
+            };
+            serde_json::from_str(&value).unwrap_or(serde_json::Value::Null)
+        };
+
+        let stride = self.entry_stride(entries_location, stride);
+        let free = self.read_free_entries(&header, stride);
+        let start = entries + constants::ARRAY_HEADER_SIZE;
+        let pairs = (0..count as usize)
+            .filter(|index| !free.contains(&(*index as u32)))
+            .map(|index| start + index * stride)
+            .filter(|entry| self.reader.maybe_read_u32(*entry).unwrap_or(u32::MAX) as i32 >= 0)
+            .map(|entry| {
+                (
+                    read(entry + key_offset, key_type),
+                    read(entry + value_offset, value_type),
+                )
+            })
+            .collect();
+
+        Some(pairs)
+    }
+
+    // Element size of the Entry[] array at entries_location, which is what
+    // the entries are really spaced by. A stride worked out from the wrong
+    // key and value sizes would read every entry after the first misaligned.
+    fn entry_stride(&self, entries_location: usize, expected: usize) -> usize {
+        let element_size = self.array_element_size(entries_location);
+
+        // smaller than the entry it is unreadable or not an Entry[], reading
+        // the key and value at their offsets would go past each element
+        if element_size <= expected {
+            return expected;
+        }
+
+        eprintln!(
+            "Dictionary entries are {} bytes, not the expected {}, using the array's element size",
+            element_size, expected
+        );
+        element_size
+    }
+
+    // Element size of the class of the array at location, 0 if unreadable
+    fn array_element_size(&self, location: usize) -> usize {
+        Managed::new(self.reader, location, None)
+            .read_class()
+            .size
+            .max(0) as usize
+    }
+
+    // Reads the entries of the Dictionary pointed by self.addr without
+    // knowing its key and value types: hashCode and next, then the rest of
+    // each entry as hex, to work out an unknown layout by hand. Removed
+    // entries are kept, their hashCode is negative.
+    pub fn read_dict_raw(&self) -> Option<Vec<serde_json::Value>> {
+        let DictionaryHeader {
+            entries_location,
+            count,
+            ..
+        } = self.read_dict_header(None)?;
+        let entries = self.reader.maybe_read_ptr(entries_location)?;
+        if entries == 0 {
+            return Some(Vec::new());
+        }
+
+        let stride = self.array_element_size(entries_location);
+        if stride < 8 {
+            return None;
+        }
+
+        let bytes = self.reader.read_bytes(
+            entries + constants::ARRAY_HEADER_SIZE,
+            count as usize * stride,
+        )?;
+
+        let entries = bytes
+            .chunks_exact(stride)
+            .map(|entry| {
+                let read_i32 = |offset: usize| {
+                    i32::from_le_bytes(entry[offset..offset + 4].try_into().unwrap())
+                };
+                let raw: String = entry[8..].iter().map(|b| format!("{:02x}", b)).collect();
+                serde_json::json!({ "hashCode": read_i32(0), "next": read_i32(4), "raw": raw })
+            })
+            .collect();
+
+        Some(entries)
+    }
+
+    // Reads up to limit elements of the array pointed by self.addr
+    pub fn read_managed_array_elements(&self, limit: u32, depth: u32) -> String {
+        self.read_managed_array_range(0, limit, depth)
+    }
+
+    // Reads up to limit elements of the array pointed by self.addr, from the
+    // element at index first
+    pub fn read_managed_array_range(&self, first: u32, limit: u32, depth: u32) -> String {
         let ptr = self.reader.read_ptr(self.addr);
         if ptr == 0 {
             return String::from("null");
@@ -137,9 +1137,25 @@ impl<'a> Managed<'a> {
         let element_definition =
             TypeDefinition::new(self.reader.read_ptr(array_definition_ptr), self.reader);
 
-        let count = self.reader.read_u32(ptr + (constants::SIZE_OF_PTR * 3));
+        let mut count = self
+            .reader
+            .read_array_length(ptr)
+            .unwrap_or(0)
+            .saturating_sub(first)
+            .min(limit);
 
-        let start = ptr + constants::SIZE_OF_PTR * 4;
+        // Expanding nested objects costs a read per field of every level,
+        // keep it bounded past the first one
+        if depth > 1 && count > constants::MAX_EXPANDED_ELEMENTS {
+            eprintln!(
+                "Array too big to expand ({}), reading the first {}",
+                count,
+                constants::MAX_EXPANDED_ELEMENTS
+            );
+            count = constants::MAX_EXPANDED_ELEMENTS;
+        }
+
+        let start = ptr + constants::ARRAY_HEADER_SIZE;
 
         let mut result = Vec::new();
 
@@ -161,15 +1177,34 @@ impl<'a> Managed<'a> {
 
         let code = element_definition.type_info.clone().code();
 
-        for i in 0..count {
+        // structs are stored inline, one after the other
+        let stride = match code {
+            TypeCode::VALUETYPE => element_definition.value_size(),
+            _ => array_definition.size as usize,
+        };
+
+        for i in first..first + count {
             let managed = Managed::new(
                 self.reader,
-                start + (i as usize * array_definition.size as usize),
+                start + (i as usize * stride),
                 Some(type_args.clone()),
             );
 
             let strout = match code {
-                TypeCode::CLASS => managed.read_class().to_string(),
+                TypeCode::VALUETYPE => managed.read_struct(&element_definition.type_info),
+                TypeCode::CLASS => {
+                    let element_ptr = self.reader.read_ptr(managed.addr);
+                    if element_ptr == 0 {
+                        String::from("null")
+                    } else if depth == 0 {
+                        element_ptr.to_string()
+                    } else {
+                        let mut class = managed.read_class();
+                        class.set_fields_base(element_ptr);
+                        class.set_depth(depth - 1);
+                        class.to_string()
+                    }
+                }
                 TypeCode::GENERICINST => {
                     let m = managed.read_generic_instance(element_definition.type_info.clone());
 
@@ -199,28 +1234,39 @@ impl<'a> Managed<'a> {
                             None => field_def.type_info.clone(),
                         };
 
+                        // the struct is stored inline in the array, without
+                        // the object header its field offsets count
                         let managed_var = Managed::new(
                             self.reader,
-                            managed.addr + (field_def.offset + offset) as usize,
+                            (managed.addr as isize + (field_def.offset + offset) as isize
+                                - constants::OBJECT_HEADER_SIZE as isize)
+                                as usize,
                             None,
                         );
 
                         let var = match gen_type.clone().code() {
                             TypeCode::I4 => managed_var.read_i4().to_string(),
                             TypeCode::U4 => managed_var.read_u4().to_string(),
-                            TypeCode::R4 => managed_var.read_r4().to_string(),
-                            TypeCode::R8 => managed_var.read_r8().to_string(),
+                            TypeCode::R4 => float_to_json(managed_var.read_r4()),
+                            TypeCode::R8 => float_to_json(managed_var.read_r8()),
                             TypeCode::I => managed_var.read_i4().to_string(),
                             TypeCode::U => managed_var.read_u4().to_string(),
                             TypeCode::I2 => managed_var.read_i2().to_string(),
                             TypeCode::U2 => managed_var.read_u2().to_string(),
-                            TypeCode::STRING => managed_var.read_string(),
+                            TypeCode::I8 => int64_to_json(managed_var.read_i8()),
+                            TypeCode::U8 => uint64_to_json(managed_var.read_u8()),
+                            TypeCode::STRING => {
+                                managed_var.read_managed_string().to_json().to_string()
+                            }
                             TypeCode::CLASS => {
                                 let mut class = managed_var.read_class();
                                 let ptr = self.reader.read_ptr(managed_var.addr);
                                 class.set_fields_base(ptr);
                                 class.to_string()
                             }
+                            // structs are stored inline in the entry, the type
+                            // argument points straight to their class
+                            TypeCode::VALUETYPE => managed_var.read_struct(&gen_type),
                             // (field_def.type_info.code()).to_string(),
                             _ => "null".to_string(),
                         };
@@ -249,6 +1295,87 @@ impl<'a> Managed<'a> {
     }
 }
 
+// Debug keeps the decimal point of whole values (1.0, not 1) so they still
+// parse as floats. NaN and the infinities have no JSON form, they are
+// emitted as null.
+pub fn float_to_json<F: Into<f64> + std::fmt::Debug + Copy>(value: F) -> String {
+    if value.into().is_finite() {
+        format!("{:?}", value)
+    } else {
+        String::from("null")
+    }
+}
+
+// JS numbers lose precision past 2^53, bigger 64 bit integers are emitted
+// as strings
+pub fn int64_to_json(value: i64) -> String {
+    if value.unsigned_abs() > constants::MAX_SAFE_INTEGER {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn uint64_to_json(value: u64) -> String {
+    if value > constants::MAX_SAFE_INTEGER {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn format_decimal(flags: u32, hi: u32, lo: u32, mid: u32) -> String {
+    let mantissa = ((hi as u128) << 64) | ((mid as u128) << 32) | lo as u128;
+    let scale = ((flags >> 16) & 0xff) as usize;
+    let sign = if flags & 0x8000_0000 != 0 { "-" } else { "" };
+
+    let digits = format!("{:0>width$}", mantissa, width = scale + 1);
+    if scale == 0 {
+        return format!("{}{}", sign, digits);
+    }
+
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+// Size of a value of the type stored inline in an object or struct, None
+// for structs whose size depends on their class
+fn inline_size(type_code: TypeCode) -> Option<usize> {
+    match type_code {
+        TypeCode::BOOLEAN | TypeCode::I1 | TypeCode::U1 => Some(1),
+        TypeCode::CHAR | TypeCode::I2 | TypeCode::U2 => Some(2),
+        TypeCode::I4 | TypeCode::U4 | TypeCode::R4 => Some(4),
+        TypeCode::I8 | TypeCode::U8 | TypeCode::R8 => Some(8),
+        TypeCode::I | TypeCode::U => Some(constants::SIZE_OF_PTR),
+        TypeCode::STRING
+        | TypeCode::CLASS
+        | TypeCode::OBJECT
+        | TypeCode::SZARRAY
+        | TypeCode::GENERICINST => Some(constants::SIZE_OF_PTR),
+        _ => None,
+    }
+}
+
+// (key offset, value offset, size) of a Dictionary<key, value>.Entry:
+// hashCode at 0 and next at 4, then the key and the value each at the
+// alignment of its size, the size rounded up to the largest alignment.
+// Dictionary<int, long> has its value at 16, not 12, and is 24 bytes.
+pub fn dictionary_entry_layout(key: TypeCode, value: TypeCode) -> Option<(usize, usize, usize)> {
+    let key_size = inline_size(key)?;
+    let value_size = inline_size(value)?;
+
+    let key_offset = align(8, key_size);
+    let value_offset = align(key_offset + key_size, value_size);
+    let stride = align(value_offset + value_size, key_size.max(value_size).max(4));
+
+    Some((key_offset, value_offset, stride))
+}
+
+// Rounds offset up to the alignment of a value of the given size
+fn align(offset: usize, size: usize) -> usize {
+    offset.div_ceil(size) * size
+}
+
 fn get_type_size(type_code: TypeCode) -> usize {
     match type_code {
         TypeCode::BOOLEAN => 1,
@@ -0,0 +1,396 @@
+// Minimal reader of the CLI metadata (ECMA-335 II.24) of an assembly image,
+// enough to get the default values of const fields, which Mono never copies
+// into the static storage of the class, and the number of fields of a type.
+use serde_json::Value;
+
+use crate::managed::{int64_to_json, uint64_to_json};
+
+// Metadata tables (II.22), only the ones laid out before Constant and the
+// ones their coded indexes point to
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD_PTR: usize = 0x03;
+const FIELD: usize = 0x04;
+const METHOD_PTR: usize = 0x05;
+const METHOD_DEF: usize = 0x06;
+const PARAM_PTR: usize = 0x07;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0a;
+const CONSTANT: usize = 0x0b;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1a;
+const TYPE_SPEC: usize = 0x1b;
+const ASSEMBLY_REF: usize = 0x23;
+
+const TABLE_COUNT: usize = 64;
+
+// "BSJB"
+const METADATA_SIGNATURE: u32 = 0x424a5342;
+
+// CLI header, entry 14 of the PE data directories
+const CLI_HEADER_DIRECTORY: usize = 14;
+
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    String,
+    Guid,
+    Blob,
+    Table(usize),
+    Coded(&'static [usize]),
+}
+
+const RESOLUTION_SCOPE: &[usize] = &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF];
+const TYPE_DEF_OR_REF: &[usize] = &[TYPE_DEF, TYPE_REF, TYPE_SPEC];
+const MEMBER_REF_PARENT: &[usize] = &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC];
+const HAS_CONSTANT: &[usize] = &[FIELD, PARAM, PROPERTY];
+
+fn table_columns(table: usize) -> &'static [Column] {
+    use Column::*;
+
+    match table {
+        MODULE => &[U16, String, Guid, Guid, Guid],
+        TYPE_REF => &[Coded(RESOLUTION_SCOPE), String, String],
+        TYPE_DEF => &[
+            U32,
+            String,
+            String,
+            Coded(TYPE_DEF_OR_REF),
+            Table(FIELD),
+            Table(METHOD_DEF),
+        ],
+        FIELD_PTR => &[Table(FIELD)],
+        FIELD => &[U16, String, Blob],
+        METHOD_PTR => &[Table(METHOD_DEF)],
+        METHOD_DEF => &[U32, U16, U16, String, Blob, Table(PARAM)],
+        PARAM_PTR => &[Table(PARAM)],
+        PARAM => &[U16, U16, String],
+        INTERFACE_IMPL => &[Table(TYPE_DEF), Coded(TYPE_DEF_OR_REF)],
+        MEMBER_REF => &[Coded(MEMBER_REF_PARENT), String, Blob],
+        // type (u8) and a padding byte, parent, value
+        CONSTANT => &[U16, Coded(HAS_CONSTANT), Blob],
+        _ => &[],
+    }
+}
+
+pub struct Metadata {
+    data: Vec<u8>,
+    rows: [u32; TABLE_COUNT],
+    table_offsets: [usize; CONSTANT + 1],
+    strings: usize,
+    blob: usize,
+    string_size: usize,
+    guid_size: usize,
+    blob_size: usize,
+}
+
+impl Metadata {
+    // Parses the metadata of a PE image as loaded by Mono (the raw file)
+    pub fn from_image(data: Vec<u8>) -> Option<Self> {
+        let root = metadata_root_offset(&data)?;
+        Self::from_metadata_root(data, root)
+    }
+
+    // Parses the metadata whose root (the "BSJB" signature) is at root
+    pub fn from_metadata_root(data: Vec<u8>, root: usize) -> Option<Self> {
+        if read_u32(&data, root)? != METADATA_SIGNATURE {
+            return None;
+        }
+
+        let version_length = read_u32(&data, root + 12)? as usize;
+        let streams_header = root + 16 + version_length;
+        let stream_count = read_u16(&data, streams_header + 2)?;
+
+        let mut tables_stream = None;
+        let mut strings = None;
+        let mut blob = None;
+
+        let mut offset = streams_header + 4;
+        for _ in 0..stream_count {
+            let stream_offset = root + read_u32(&data, offset)? as usize;
+            let name_start = offset + 8;
+            let name_length = data.get(name_start..)?.iter().position(|b| *b == 0)?;
+            let name = std::str::from_utf8(&data[name_start..name_start + name_length]).ok()?;
+
+            match name {
+                "#~" => tables_stream = Some(stream_offset),
+                "#Strings" => strings = Some(stream_offset),
+                "#Blob" => blob = Some(stream_offset),
+                _ => {}
+            }
+
+            // the name is null terminated and padded to 4 bytes
+            offset = name_start + (name_length + 4) / 4 * 4;
+        }
+
+        // uncompressed (#-) tables are not supported
+        let tables_stream = tables_stream?;
+
+        let heap_sizes = *data.get(tables_stream + 6)?;
+        let valid = read_u64(&data, tables_stream + 8)?;
+
+        let mut rows = [0u32; TABLE_COUNT];
+        let mut offset = tables_stream + 24;
+        for (table, table_rows) in rows.iter_mut().enumerate() {
+            if valid & (1 << table) != 0 {
+                *table_rows = read_u32(&data, offset)?;
+                offset += 4;
+            }
+        }
+
+        let mut metadata = Metadata {
+            data,
+            rows,
+            table_offsets: [0; CONSTANT + 1],
+            strings: strings?,
+            blob: blob?,
+            string_size: if heap_sizes & 0x1 != 0 { 4 } else { 2 },
+            guid_size: if heap_sizes & 0x2 != 0 { 4 } else { 2 },
+            blob_size: if heap_sizes & 0x4 != 0 { 4 } else { 2 },
+        };
+
+        for table in 0..=CONSTANT {
+            metadata.table_offsets[table] = offset;
+            offset += metadata.row_size(table) * metadata.rows[table] as usize;
+        }
+
+        Some(metadata)
+    }
+
+    // Default values of the const fields of the type with the given token
+    // (MonoClass.type_token), as (field name, value)
+    pub fn constant_values(&self, type_token: u32) -> Vec<(String, Value)> {
+        let (first_field, end_field) = match self.field_range(type_token) {
+            Some(range) => range,
+            None => return Vec::new(),
+        };
+
+        let mut values = Vec::new();
+        for constant in 1..=self.rows[CONSTANT] {
+            let parent = self.column(CONSTANT, constant, 1).unwrap_or(0);
+            let field = parent >> 2;
+
+            // HasConstant tag 0 is a Field
+            if parent & 0x3 != 0 || field < first_field || field >= end_field {
+                continue;
+            }
+
+            let name = self.string(self.column(FIELD, field, 1).unwrap_or(0));
+            let element_type = self.column(CONSTANT, constant, 0).unwrap_or(0) as u8;
+            let value = self
+                .blob(self.column(CONSTANT, constant, 2).unwrap_or(0))
+                .and_then(|blob| decode_constant(element_type, blob));
+
+            if let (Some(name), Some(value)) = (name, value) {
+                values.push((name, value));
+            }
+        }
+
+        values
+    }
+
+    // Number of fields the type with the given token declares, statics and
+    // consts included
+    pub fn field_count(&self, type_token: u32) -> Option<u32> {
+        let (first_field, end_field) = self.field_range(type_token)?;
+        Some(end_field.saturating_sub(first_field))
+    }
+
+    // Rows of the Field table of the type with the given token, its field
+    // list runs up to the field list of the next type
+    fn field_range(&self, type_token: u32) -> Option<(u32, u32)> {
+        let type_row = type_token & 0x00ff_ffff;
+        if type_token >> 24 != TYPE_DEF as u32 || type_row == 0 || type_row > self.rows[TYPE_DEF] {
+            return None;
+        }
+
+        // field rows start at 1, a 0 is a corrupt or misread FieldList
+        let first_field = self.column(TYPE_DEF, type_row, 4)?;
+        if first_field == 0 {
+            return None;
+        }
+        let end_field = if type_row < self.rows[TYPE_DEF] {
+            self.column(TYPE_DEF, type_row + 1, 4)?
+        } else {
+            self.rows[FIELD] + 1
+        };
+
+        Some((first_field, end_field))
+    }
+
+    fn index_size(&self, tables: &[usize]) -> usize {
+        let tag_bits = usize::BITS - (tables.len() - 1).leading_zeros();
+        let max_rows = tables
+            .iter()
+            .map(|table| self.rows[*table])
+            .max()
+            .unwrap_or(0);
+
+        if max_rows < 1 << (16 - tag_bits) {
+            2
+        } else {
+            4
+        }
+    }
+
+    fn column_size(&self, column: Column) -> usize {
+        match column {
+            Column::U16 => 2,
+            Column::U32 => 4,
+            Column::String => self.string_size,
+            Column::Guid => self.guid_size,
+            Column::Blob => self.blob_size,
+            Column::Table(table) => {
+                if self.rows[table] < 1 << 16 {
+                    2
+                } else {
+                    4
+                }
+            }
+            Column::Coded(tables) => self.index_size(tables),
+        }
+    }
+
+    fn row_size(&self, table: usize) -> usize {
+        table_columns(table)
+            .iter()
+            .map(|column| self.column_size(*column))
+            .sum()
+    }
+
+    // Value of a column of a row (rows start at 1)
+    fn column(&self, table: usize, row: u32, column: usize) -> Option<u32> {
+        let columns = table_columns(table);
+        let offset = self.table_offsets[table]
+            + self.row_size(table) * row.checked_sub(1)? as usize
+            + columns[..column]
+                .iter()
+                .map(|column| self.column_size(*column))
+                .sum::<usize>();
+
+        match self.column_size(columns[column]) {
+            2 => read_u16(&self.data, offset).map(|value| value as u32),
+            _ => read_u32(&self.data, offset),
+        }
+    }
+
+    fn string(&self, index: u32) -> Option<String> {
+        let start = self.strings + index as usize;
+        let length = self.data.get(start..)?.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&self.data[start..start + length]).to_string())
+    }
+
+    fn blob(&self, index: u32) -> Option<&[u8]> {
+        let start = self.blob + index as usize;
+        let first = *self.data.get(start)?;
+
+        // compressed length (II.23.2)
+        let (length, header) = if first & 0x80 == 0 {
+            (first as usize, 1)
+        } else if first & 0xc0 == 0x80 {
+            (
+                (((first & 0x3f) as usize) << 8) | *self.data.get(start + 1)? as usize,
+                2,
+            )
+        } else {
+            let bytes = self.data.get(start..start + 4)?;
+            (
+                (((first & 0x1f) as usize) << 24)
+                    | ((bytes[1] as usize) << 16)
+                    | ((bytes[2] as usize) << 8)
+                    | bytes[3] as usize,
+                4,
+            )
+        };
+
+        self.data.get(start + header..start + header + length)
+    }
+}
+
+// The value of a Constant row, element_type is the ELEMENT_TYPE of the field
+fn decode_constant(element_type: u8, blob: &[u8]) -> Option<Value> {
+    let json = match element_type {
+        0x02 => (*blob.first()? != 0).to_string(),
+        0x03 => {
+            let c = u16::from_le_bytes(blob.get(..2)?.try_into().ok()?);
+            Value::String(String::from_utf16_lossy(&[c])).to_string()
+        }
+        0x04 => (*blob.first()? as i8).to_string(),
+        0x05 => blob.first()?.to_string(),
+        0x06 => i16::from_le_bytes(blob.get(..2)?.try_into().ok()?).to_string(),
+        0x07 => u16::from_le_bytes(blob.get(..2)?.try_into().ok()?).to_string(),
+        0x08 => i32::from_le_bytes(blob.get(..4)?.try_into().ok()?).to_string(),
+        0x09 => u32::from_le_bytes(blob.get(..4)?.try_into().ok()?).to_string(),
+        0x0a => int64_to_json(i64::from_le_bytes(blob.get(..8)?.try_into().ok()?)),
+        0x0b => uint64_to_json(u64::from_le_bytes(blob.get(..8)?.try_into().ok()?)),
+        0x0c => f32::from_le_bytes(blob.get(..4)?.try_into().ok()?).to_string(),
+        0x0d => f64::from_le_bytes(blob.get(..8)?.try_into().ok()?).to_string(),
+        0x0e => {
+            let chars: Vec<u16> = blob
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            Value::String(String::from_utf16_lossy(&chars)).to_string()
+        }
+        // null reference
+        0x12 => "null".to_string(),
+        _ => return None,
+    };
+
+    serde_json::from_str(&json).ok()
+}
+
+// File offset of the metadata root of a PE image
+fn metadata_root_offset(data: &[u8]) -> Option<usize> {
+    let pe = read_u32(data, 0x3c)? as usize;
+    let section_count = read_u16(data, pe + 6)? as usize;
+    let optional_header_size = read_u16(data, pe + 20)? as usize;
+    let optional_header = pe + 24;
+
+    // PE32 or PE32+
+    let directories = match read_u16(data, optional_header)? {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        _ => return None,
+    };
+
+    let sections = optional_header + optional_header_size;
+    let rva_to_offset = |rva: u32| {
+        (0..section_count).find_map(|section| {
+            let header = sections + section * 40;
+            let virtual_size = read_u32(data, header + 8)?;
+            let virtual_address = read_u32(data, header + 12)?;
+            let raw_data = read_u32(data, header + 20)?;
+
+            if rva >= virtual_address && rva < virtual_address + virtual_size {
+                Some((rva - virtual_address + raw_data) as usize)
+            } else {
+                None
+            }
+        })
+    };
+
+    let cli_header = rva_to_offset(read_u32(data, directories + CLI_HEADER_DIRECTORY * 8)?)?;
+    rva_to_offset(read_u32(data, cli_header + 8)?)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
@@ -1,24 +1,219 @@
-#[cfg(target_os = "windows")]
-use proc_mem::{ProcMemError, Process};
-
 #[cfg(target_os = "windows")]
 use is_elevated::is_elevated;
 
 #[cfg(target_os = "linux")]
 use sudo::RunningAs;
 
-use sysinfo::{Pid, System};
+use sysinfo::{Pid, ProcessStatus, System};
 
-use process_memory::{DataMember, Memory, ProcessHandle, TryIntoProcessHandle};
+use process_memory::{
+    CopyAddress, DataMember, Memory, ProcessHandle, ProcessHandleExt, TryIntoProcessHandle,
+};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
 use crate::constants;
+use crate::field_definition::FieldDefinition;
 use crate::pe_reader::PEReader;
+use crate::type_definition::TypeDefinition;
+
+// Totals of every read done by any reader in this process, a baseline to
+// measure read throughput against
+static READ_COUNT: AtomicU64 = AtomicU64::new(0);
+static READ_BYTES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReadStats {
+    pub reads: u64,
+    pub bytes: u64,
+}
+
+fn record_read(bytes: usize) {
+    READ_COUNT.fetch_add(1, Ordering::Relaxed);
+    READ_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+// Number of classes whose names are checked by validate
+const VALIDATE_CLASS_COUNT: usize = 16;
+
+// Class the reads of the game start from, it's loaded with the rest of
+// Assembly-CSharp
+const READY_CLASS: &str = "WrapperController";
+
+// Reads that fail are retried a page at a time to keep what can be read
+const PAGE_SIZE: usize = 0x1000;
+
+// C strings are read this many bytes at a time, most names fit in one
+const C_STRING_CHUNK: usize = 64;
+
+// Longest C string read, past it the string is cut
+const MAX_C_STRING_LENGTH: usize = 1025;
+
+// Buckets of the class cache hash table, far more than any assembly needs
+const MAX_CLASS_CACHE_SIZE: u32 = 0x100000;
+
+// Class names are identifiers, plus the characters the compiler uses in the
+// names of generic and generated types
+pub fn is_plausible_class_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_`<>{}.$=-".contains(c))
+}
+
+// Names in the metadata are UTF-8, invalid bytes become U+FFFD instead of
+// Latin-1 characters
+pub fn decode_c_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+// Position of a module file name in MONO_LIBRARIES, past the end when it
+// isn't a mono library. Paths are matched on their file name.
+fn mono_library_index(module: &str) -> usize {
+    let file_name = module.rsplit(['/', '\\']).next().unwrap_or(module);
+
+    constants::MONO_LIBRARIES
+        .iter()
+        .position(|library| library.eq_ignore_ascii_case(file_name))
+        .unwrap_or(constants::MONO_LIBRARIES.len())
+}
+
+#[cfg(target_os = "windows")]
+fn until_nul(chars: &[u16]) -> &[u16] {
+    let len = chars.iter().position(|c| *c == 0).unwrap_or(chars.len());
+    &chars[..len]
+}
+
+// The files mapped in a /proc/<pid>/maps listing, as (path, base address).
+// Wine maps the PE files like any other file, the base is the mapping of
+// their first page.
+#[cfg(target_os = "linux")]
+pub fn modules_in_maps(maps: &str) -> Vec<(String, usize)> {
+    let mut modules: Vec<(String, usize)> = Vec::new();
+
+    for line in maps.lines() {
+        // start-end perms offset dev inode path
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 || fields[2].bytes().any(|b| b != b'0') {
+            continue;
+        }
+
+        let path = fields[5..].join(" ");
+        if modules.iter().any(|(module, _)| *module == path) {
+            continue;
+        }
+
+        let start = fields[0].split('-').next().unwrap_or("");
+        if let Ok(base) = usize::from_str_radix(start, 16) {
+            modules.push((path, base));
+        }
+    }
+
+    modules
+}
+
+// The modules loaded in the process, as (path, base address), the main
+// executable first
+#[cfg(target_os = "windows")]
+fn loaded_modules(pid: u32) -> Vec<(String, usize)> {
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::tlhelp32::{
+        CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, MODULEENTRY32W, TH32CS_SNAPMODULE,
+        TH32CS_SNAPMODULE32,
+    };
+
+    let mut modules = Vec::new();
+
+    // the snapshot lists every module, several can share a file name
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32, pid);
+        if snapshot == INVALID_HANDLE_VALUE {
+            eprintln!("Error listing the modules of process {}", pid);
+            return modules;
+        }
+
+        let mut entry: MODULEENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<MODULEENTRY32W>() as u32;
+
+        let mut more = Module32FirstW(snapshot, &mut entry) != 0;
+        while more {
+            let path = String::from_utf16_lossy(until_nul(&entry.szExePath));
+            modules.push((path, entry.modBaseAddr as usize));
+            more = Module32NextW(snapshot, &mut entry) != 0;
+        }
+
+        CloseHandle(snapshot);
+    }
+
+    modules
+}
+
+#[cfg(target_os = "linux")]
+fn loaded_modules(pid: u32) -> Vec<(String, usize)> {
+    match std::fs::read_to_string(format!("/proc/{}/maps", pid)) {
+        Ok(maps) => modules_in_maps(&maps),
+        Err(e) => {
+            eprintln!("Error reading the maps of process {}: {:?}", pid, e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn loaded_modules(_pid: u32) -> Vec<(String, usize)> {
+    Vec::new()
+}
+
+// Whether a word of the process memory can be read, at the base of its
+// first module. None when its modules can't be listed.
+fn memory_readable(pid: u32) -> Option<bool> {
+    let (_, base) = loaded_modules(pid).into_iter().next()?;
+    let handle = match (pid as process_memory::Pid).try_into_process_handle() {
+        Ok(handle) => handle,
+        Err(_) => return Some(false),
+    };
+
+    let mut word = [0u8; constants::SIZE_OF_PTR];
+    let readable = handle.copy_address(base, &mut word).is_ok();
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        winapi::um::handleapi::CloseHandle(handle.0);
+    }
+
+    Some(readable)
+}
+
+// The process to read among the ones matching a name, given as (pid, status,
+// memory, readable): not a zombie or dead, with memory that can be read, or
+// that has some when it can't be checked. The one using the most memory
+// when several are left, a launcher or crash handler uses less than the game.
+pub fn pick_running_process(matches: &[(Pid, ProcessStatus, u64, Option<bool>)]) -> Option<Pid> {
+    matches
+        .iter()
+        .filter(|(_, status, memory, readable)| {
+            !matches!(status, ProcessStatus::Zombie | ProcessStatus::Dead)
+                && readable.unwrap_or(*memory > 0)
+        })
+        .max_by_key(|(_, _, memory, _)| *memory)
+        .map(|(pid, ..)| *pid)
+}
+
+// (class name, MonoClassField, definition) of a field looked up by name
+type CachedField = (String, usize, FieldDefinition);
 
 pub struct MonoReader {
     pid: u32,
     handle: ProcessHandle,
     mono_root_domain: usize,
     assembly_image_address: usize,
+    // fields already looked up by (class, name), a class doesn't move its
+    // fields once loaded
+    field_cache: Mutex<HashMap<(usize, String), CachedField>>,
+    // read_ methods that fell back to 0
+    failed_reads: AtomicU64,
 }
 
 impl MonoReader {
@@ -32,17 +227,87 @@ impl MonoReader {
             handle,
             mono_root_domain: 0,
             assembly_image_address: 0,
+            field_cache: Mutex::new(HashMap::new()),
+            failed_reads: AtomicU64::new(0),
         }
     }
 
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    // Closes the handle to the process. Any read done after this fails
+    // cleanly instead of going through a dangling handle.
+    pub fn close(&mut self) {
+        #[cfg(target_os = "windows")]
+        {
+            // process_memory opens the handle with OpenProcess but never
+            // closes it, so we have to do it ourselves
+            if self.handle.check_handle() {
+                unsafe {
+                    winapi::um::handleapi::CloseHandle(self.handle.0);
+                }
+            }
+        }
+
+        self.handle = ProcessHandle::null_type();
+    }
+
+    // Number of reads and bytes read since the process started
+    pub fn stats() -> ReadStats {
+        ReadStats {
+            reads: READ_COUNT.load(Ordering::Relaxed),
+            bytes: READ_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    // A game that just exited can linger as a zombie with no memory left to
+    // read, so only live processes with resident memory count. When several
+    // match, the one using the most memory is the game, not a launcher.
     pub fn find_pid_by_name(name: &str) -> Option<Pid> {
         let mut sys = System::new_all();
         sys.refresh_all();
 
-        sys.processes()
+        let matches: Vec<_> = sys
+            .processes()
             .iter()
-            .find(|(_, process)| process.name().contains(name))
-            .map(|(pid, _)| *pid)
+            .filter(|(_, process)| process.name().contains(name))
+            .map(|(pid, process)| {
+                (
+                    *pid,
+                    process.status(),
+                    process.memory(),
+                    memory_readable(pid.as_u32()),
+                )
+            })
+            .collect();
+
+        let found = pick_running_process(&matches);
+
+        if found.is_none() && !matches.is_empty() {
+            eprintln!(
+                "{} process(es) named {} found, none running with readable memory",
+                matches.len(),
+                name
+            );
+        }
+
+        found
+    }
+
+    // Path of the executable of the process, None if we can't see it
+    pub fn exe_path(pid: u32) -> Option<String> {
+        let mut sys = System::new();
+        let pid = Pid::from_u32(pid);
+        sys.refresh_process(pid);
+
+        let exe = sys.process(pid)?.exe()?;
+        Some(exe.to_string_lossy().to_string())
+    }
+
+    pub fn pid_exists(pid: u32) -> bool {
+        let mut sys = System::new();
+        sys.refresh_process(Pid::from_u32(pid))
     }
 
     pub fn is_admin() -> bool {
@@ -56,55 +321,110 @@ impl MonoReader {
         }
     }
 
-    #[cfg(target_os = "windows")]
+    // The mono_get_root_domain of the first mono library loaded, see
+    // select_mono_runtime when the process has several of them
     pub fn read_mono_root_domain(&mut self) -> usize {
-        let mtga_process = match Process::with_pid(*&self.pid) {
-            Ok(process) => Some(process),
-            Err(e) => {
-                eprintln!("Error obtaining process data: {:?}", e);
-                None
-            }
+        self.mono_root_domain = self
+            .root_domain_candidates()
+            .first()
+            .map(|(_, root_domain)| *root_domain)
+            .unwrap_or(0);
+
+        if self.mono_root_domain == 0 {
+            eprintln!(
+                "Error: mono_get_root_domain not found in any of {:?}",
+                constants::MONO_LIBRARIES
+            );
         }
-        .unwrap();
 
-        let module = match mtga_process.module(constants::MONO_LIBRARY) {
-            Ok(module) => Some(module),
-            Err(ProcMemError::ModuleNotFound) => None,
-            Err(e) => {
-                eprintln!("Error obtaining mono dll: {:?}", e);
-                None
+        println!("mono_root_domain addr: {:x?}", self.mono_root_domain);
+        self.mono_root_domain
+    }
+
+    // Reads the root domain and Assembly-CSharp from the mono runtime of the
+    // game. Some setups (Proton, injected tools) load more than one runtime,
+    // the first one can give a garbage root domain, so every one is tried
+    // until Assembly-CSharp resolves.
+    pub fn select_mono_runtime(&mut self) -> Result<usize, String> {
+        let candidates = self.root_domain_candidates();
+
+        for (module, root_domain) in &candidates {
+            self.mono_root_domain = *root_domain;
+            self.assembly_image_address = 0;
+
+            if self.read_assembly_image() != 0 {
+                println!("Using the mono runtime of {}", module);
+                return Ok(self.assembly_image_address);
             }
         }
-        .unwrap();
 
-        // println!("mono-2.0-bdwgc.dll Base addr: {:x?}", module.base_address());
+        self.mono_root_domain = 0;
 
-        let pe = PEReader::new(&self, module.base_address() as usize);
+        if candidates.is_empty() {
+            return Err(format!(
+                "Mono root domain not found, no mono_get_root_domain export in {:?}",
+                constants::MONO_LIBRARIES
+            ));
+        }
 
-        let mono_root_offset = pe.get_function_offset("mono_get_root_domain");
+        let candidates: Vec<String> = candidates
+            .iter()
+            .map(|(module, root_domain)| format!("{} ({:x})", module, root_domain))
+            .collect();
+        Err(format!(
+            "Assembly-CSharp not found in any mono runtime loaded: {}",
+            candidates.join(", ")
+        ))
+    }
 
-        println!("mono_get_root_domain offset: {:?}", mono_root_offset);
+    // mono_get_root_domain of every mono library loaded, as (module, address),
+    // in the order of MONO_LIBRARIES
+    fn root_domain_candidates(&self) -> Vec<(String, usize)> {
+        let mut modules = self.mono_modules();
+        modules.sort_by_key(|(module, _)| mono_library_index(module));
 
-        match mono_root_offset {
-            Ok(offset) => {
-                self.mono_root_domain = module.base_address() as usize + offset as usize;
-            }
-            _ => {
-                eprintln!("Error: mono_get_root_domain not found");
-            }
-        }
+        let candidates: Vec<(String, usize)> = modules
+            .into_iter()
+            .filter_map(|(module, base)| {
+                let pe = PEReader::new(self, base);
 
-        println!("mono_root_domain addr: {:x?}", self.mono_root_domain);
-        self.mono_root_domain
+                match pe.get_function_offset("mono_get_root_domain") {
+                    Ok(offset) => Some((module, base + offset as usize)),
+                    _ => {
+                        eprintln!("{} loaded but mono_get_root_domain not exported", module);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        #[cfg(target_os = "linux")]
+        let candidates = if candidates.is_empty() {
+            vec![("memory scan".to_string(), self.scan_mono_root_domain())]
+        } else {
+            candidates
+        };
+
+        candidates
     }
 
+    // The mono libraries loaded in the process, as (path, base address)
+    fn mono_modules(&self) -> Vec<(String, usize)> {
+        loaded_modules(self.pid)
+            .into_iter()
+            .filter(|(path, _)| mono_library_index(path) < constants::MONO_LIBRARIES.len())
+            .collect()
+    }
+
+    // Last resort when no mono library shows in the maps of the process
     #[cfg(target_os = "linux")]
-    pub fn read_mono_root_domain(&mut self) -> usize {
+    fn scan_mono_root_domain(&self) -> usize {
         // walk trough the memory of the process to find the mono root domain
         // we use the PE header magic number (MZ) to find the mono library
 
         let mut addr = 0 as usize;
         let mut found = false;
+        let mut mono_root_domain = 0;
         let mut managed = DataMember::<u16>::new(self.handle);
 
         println!("Searching for mono library...");
@@ -112,6 +432,7 @@ impl MonoReader {
         while !found {
             let val = unsafe {
                 managed.set_offset(vec![addr]);
+                record_read(std::mem::size_of::<u16>());
                 match managed.read() {
                     Ok(val) => val,
                     Err(_e) => 0,
@@ -127,7 +448,7 @@ impl MonoReader {
                 match mono_root_offset {
                     Ok(offset) => {
                         println!("mono_get_root_domain offset: {:?}", offset);
-                        self.mono_root_domain = addr + offset as usize;
+                        mono_root_domain = addr + offset as usize;
                         found = true
                     }
                     _ => {
@@ -139,29 +460,45 @@ impl MonoReader {
             addr += 4096;
         }
 
-        println!("mono_root_domain addr: {:x?}", self.mono_root_domain);
-        self.mono_root_domain
+        mono_root_domain
     }
 
-    #[cfg(target_os = "macos")]
-    pub fn read_mono_root_domain(&mut self) -> usize {
-        self.mono_root_domain = 0 as usize;
+    pub fn create_type_definitions(&mut self) -> Result<Vec<usize>, String> {
+        if self.assembly_image_address == 0 {
+            return Err(
+                "No assembly image, read_assembly_image has to run before create_type_definitions"
+                    .to_string(),
+            );
+        }
 
-        self.mono_root_domain
+        self.create_type_definitions_for_image(self.assembly_image_address)
     }
 
-    pub fn create_type_definitions(&mut self) -> Vec<usize> {
+    pub fn create_type_definitions_for_image(
+        &self,
+        image_address: usize,
+    ) -> Result<Vec<usize>, String> {
         // let type_definitions = Vec::new();
 
         let class_cache_size = self.read_u32(
-            self.assembly_image_address
-                + (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_SIZE) as usize,
+            image_address + (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_SIZE) as usize,
         );
         let class_cache_table_array = self.read_ptr(
-            self.assembly_image_address
-                + (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_TABLE) as usize,
+            image_address + (constants::IMAGE_CLASS_CACHE + constants::HASH_TABLE_TABLE) as usize,
         );
 
+        // A wrong class_cache offset reads zeros or garbage here, better say
+        // so than return no classes
+        if class_cache_size == 0
+            || class_cache_size > MAX_CLASS_CACHE_SIZE
+            || class_cache_table_array == 0
+        {
+            return Err(format!(
+                "Class cache of the assembly image at {:x} is unreadable (size {}, table {:x}), the image_class_cache offset may not match this Unity version",
+                image_address, class_cache_size, class_cache_table_array
+            ));
+        }
+
         // println!("Class cache size: {:?}", class_cache_size);
 
         // println!("Class cache table array: {:?}", class_cache_table_array);
@@ -191,10 +528,23 @@ impl MonoReader {
             table_item += constants::SIZE_OF_PTR as u32;
         }
 
-        return type_defs;
+        return Ok(type_defs);
     }
 
-    pub fn read_assembly_image(&mut self) -> usize {
+    // Address of the instance of a singleton class of Assembly-CSharp, like
+    // WrapperController or PAPA
+    pub fn find_singleton_instance(&self, class_name: &str) -> Option<usize> {
+        let defs = self
+            .create_type_definitions_for_image(self.assembly_image_address)
+            .ok()?;
+
+        defs.into_iter()
+            .map(|def| TypeDefinition::new(def, self))
+            .find(|class| class.name == class_name)?
+            .get_singleton_instance()
+    }
+
+    pub fn read_domain(&self) -> usize {
         let offset = self.read_i32(self.mono_root_domain + constants::RIP_PLUS_OFFSET_OFFSET)
             + constants::RIP_VALUE_OFFSET as i32;
 
@@ -204,9 +554,18 @@ impl MonoReader {
 
         println!("Domain address: {:x?}", domain);
 
+        domain
+    }
+
+    // Lists the assemblies loaded in the root domain, as (name, image address)
+    pub fn get_assemblies(&self) -> Vec<(String, usize)> {
+        let domain = self.read_domain();
+
         let assembly_array_address =
             self.read_ptr(domain + constants::REFERENCED_ASSEMBLIES as usize);
 
+        let mut assemblies = Vec::new();
+
         let mut assembly_address = assembly_array_address;
 
         while assembly_address != 0 {
@@ -219,13 +578,9 @@ impl MonoReader {
 
             match maybe_name {
                 Some(assembly_name) => {
-                    if assembly_name == "Assembly-CSharp" {
-                        println!("Assembly name: {:?}", assembly_name);
-                        println!("  - {:?}", assembly_name_address);
-                        self.assembly_image_address =
-                            self.read_ptr(assembly + constants::ASSEMBLY_IMAGE as usize);
-                        return self.assembly_image_address;
-                    }
+                    let image_address =
+                        self.read_ptr(assembly + constants::ASSEMBLY_IMAGE as usize);
+                    assemblies.push((assembly_name, image_address));
                 }
                 None => {
                     eprintln!("Error reading assembly name");
@@ -235,14 +590,139 @@ impl MonoReader {
             assembly_address = self.read_ptr(assembly_address + constants::SIZE_OF_PTR as usize);
         }
 
+        return assemblies;
+    }
+
+    pub fn read_assembly_image(&mut self) -> usize {
+        // the classes of another image or a restarted game are not the ones
+        // cached
+        self.field_cache.lock().unwrap().clear();
+
+        let assembly = self
+            .get_assemblies()
+            .into_iter()
+            .find(|(name, _)| name == "Assembly-CSharp");
+
+        if let Some((assembly_name, image_address)) = assembly {
+            println!("Assembly name: {:?}", assembly_name);
+            println!("  - {:?}", image_address);
+            self.assembly_image_address = image_address;
+        }
+
         return self.assembly_image_address;
     }
 
+    // Checks the offsets produce sane data on the attached process, a Unity
+    // version mismatch otherwise shows up as garbage in every later read
+    pub fn validate(&mut self) -> Result<(), String> {
+        if self.mono_root_domain == 0 {
+            return Err(format!(
+                "Mono root domain not found, no mono_get_root_domain export in {:?}",
+                constants::MONO_LIBRARIES
+            ));
+        }
+
+        if self.assembly_image_address == 0 {
+            return Err(
+                "Assembly-CSharp not found, the offsets may not match this Unity version"
+                    .to_string(),
+            );
+        }
+
+        let defs = self.create_type_definitions()?;
+        let names_ok = !defs.is_empty()
+            && defs.iter().take(VALIDATE_CLASS_COUNT).all(|def| {
+                let name =
+                    self.read_ptr_ascii_string(def + constants::TYPE_DEFINITION_NAME as usize);
+                is_plausible_class_name(&name)
+            });
+
+        if !names_ok {
+            return Err(
+                "Class names don't look valid, the offsets may not match this Unity version"
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    // Whether the game got far enough in its startup to be read. Right after
+    // launch the root domain and Assembly-CSharp can be missing, and the
+    // reads come back empty instead of failing.
+    pub fn is_ready(&self) -> bool {
+        if self.mono_root_domain == 0 || self.assembly_image_address == 0 {
+            return false;
+        }
+
+        match self.create_type_definitions_for_image(self.assembly_image_address) {
+            Ok(defs) => defs.iter().any(|def| {
+                self.read_ptr_ascii_string(def + constants::TYPE_DEFINITION_NAME as usize)
+                    == READY_CLASS
+            }),
+            Err(_) => false,
+        }
+    }
+
+    // The field cached for the class at that address, if that class still
+    // has the same name and the field too. A domain reload can put another
+    // class at the address, the stale entry is dropped for a new lookup.
+    pub fn cached_field(
+        &self,
+        class: usize,
+        class_name: &str,
+        field_name: &str,
+    ) -> Option<(usize, FieldDefinition)> {
+        let key = (class, field_name.to_string());
+        let mut cache = self.field_cache.lock().unwrap();
+        let (cached_class_name, field, def) = cache.get(&key)?;
+
+        let still_valid = cached_class_name == class_name
+            && self
+                .maybe_read_ptr(field + constants::SIZE_OF_PTR)
+                .and_then(|name| self.maybe_read_ascii_string(name))
+                .is_some_and(|name| name == def.name);
+        if !still_valid {
+            cache.remove(&key);
+            return None;
+        }
+
+        Some((*field, def.clone()))
+    }
+
+    pub fn cache_field(
+        &self,
+        class: usize,
+        class_name: &str,
+        field_name: &str,
+        field: usize,
+        def: &FieldDefinition,
+    ) {
+        self.field_cache.lock().unwrap().insert(
+            (class, field_name.to_string()),
+            (class_name.to_string(), field, def.clone()),
+        );
+    }
+
+    // Number of read_ methods of this reader that returned 0 because the
+    // memory couldn't be read. The maybe_read_ ones leave the failure to
+    // their caller and aren't counted.
+    pub fn failed_reads(&self) -> u64 {
+        self.failed_reads.load(Ordering::Relaxed)
+    }
+
+    fn read_failed(&self) {
+        eprintln!("Error: {:?}", std::io::Error::last_os_error());
+        self.failed_reads.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn maybe_read_u8(&self, addr: usize) -> Option<u8> {
         let mut member = DataMember::<u8>::new(self.handle);
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<u8>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => Some(val),
@@ -257,7 +737,7 @@ impl MonoReader {
         let val = match self.maybe_read_u8(addr) {
             Some(val) => val,
             None => {
-                eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                self.read_failed();
                 0
             }
         };
@@ -265,18 +745,29 @@ impl MonoReader {
         return val;
     }
 
-    pub fn read_u16(&self, addr: usize) -> u16 {
+    pub fn maybe_read_u16(&self, addr: usize) -> Option<u16> {
         let mut member = DataMember::<u16>::new(self.handle);
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<u16>());
+
         let val = unsafe {
             match member.read() {
-                Ok(val) => val,
-                Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
-                    0
-                }
+                Ok(val) => Some(val),
+                Err(_e) => None,
+            }
+        };
+
+        return val;
+    }
+
+    pub fn read_u16(&self, addr: usize) -> u16 {
+        let val = match self.maybe_read_u16(addr) {
+            Some(val) => val,
+            None => {
+                self.read_failed();
+                0
             }
         };
 
@@ -290,6 +781,8 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<u32>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => Some(val),
@@ -304,7 +797,7 @@ impl MonoReader {
         let val = match self.maybe_read_u32(addr) {
             Some(val) => val,
             None => {
-                eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                self.read_failed();
                 0
             }
         };
@@ -317,11 +810,13 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<u64>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => val,
                 Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                    self.read_failed();
                     0
                 }
             }
@@ -335,11 +830,13 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<i8>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => val,
                 Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                    self.read_failed();
                     0
                 }
             }
@@ -353,11 +850,13 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<i16>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => val,
                 Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                    self.read_failed();
                     0
                 }
             }
@@ -371,11 +870,13 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<i32>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => val,
                 Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                    self.read_failed();
                     0
                 }
             }
@@ -389,11 +890,13 @@ impl MonoReader {
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<i64>());
+
         let val = unsafe {
             match member.read() {
                 Ok(val) => val,
                 Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
+                    self.read_failed();
                     0
                 }
             }
@@ -402,63 +905,105 @@ impl MonoReader {
         return val;
     }
 
-    pub fn read_ptr(&self, addr: usize) -> usize {
+    pub fn maybe_read_ptr(&self, addr: usize) -> Option<usize> {
         let mut member = DataMember::<usize>::new(self.handle);
 
         member.set_offset(vec![addr as usize]);
 
+        record_read(std::mem::size_of::<usize>());
+
         let val = unsafe {
             match member.read() {
-                Ok(val) => val,
-                Err(_e) => {
-                    eprintln!("Error: {:?}", std::io::Error::last_os_error());
-                    0
-                }
+                Ok(val) => Some(val),
+                Err(_e) => None,
             }
         };
 
         return val;
     }
 
-    // This methos will throw and error if the address is not readable
-    pub fn maybe_read_ascii_string(&self, addr: usize) -> Option<String> {
-        let mut string = String::new();
-        let mut index = 0;
-        loop {
-            let val = self.maybe_read_u8(addr + index);
-            match val {
-                Some(val) => {
-                    if val == 0 || index > 1024 {
-                        break;
-                    }
-                    string.push(val as char);
-                    index += 1;
+    pub fn read_ptr(&self, addr: usize) -> usize {
+        let val = match self.maybe_read_ptr(addr) {
+            Some(val) => val,
+            None => {
+                self.read_failed();
+                0
+            }
+        };
+
+        return val;
+    }
+
+    pub fn read_bytes(&self, addr: usize, length: usize) -> Option<Vec<u8>> {
+        let mut buf = vec![0; length];
+        record_read(length);
+
+        match self.handle.copy_address(addr, &mut buf) {
+            Ok(_) => Some(buf),
+            Err(_e) => None,
+        }
+    }
+
+    // Reads into buf, for loops that can reuse one buffer instead of
+    // allocating on every read. Returns how many bytes were read, from the
+    // first unreadable page on nothing is.
+    pub fn read_bytes_into(&self, addr: usize, buf: &mut [u8]) -> usize {
+        record_read(buf.len());
+        if self.handle.copy_address(addr, buf).is_ok() {
+            return buf.len();
+        }
+
+        let mut read = 0;
+        while read < buf.len() {
+            let page_end = (addr + read) / PAGE_SIZE * PAGE_SIZE + PAGE_SIZE;
+            let end = (page_end - addr).min(buf.len());
+            if self
+                .handle
+                .copy_address(addr + read, &mut buf[read..end])
+                .is_err()
+            {
+                break;
+            }
+            read = end;
+        }
+
+        read
+    }
+
+    // Bytes of the NUL terminated string at addr, up to the first byte that
+    // can't be read
+    fn read_c_string_bytes(&self, addr: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut chunk = [0u8; C_STRING_CHUNK];
+
+        while bytes.len() < MAX_C_STRING_LENGTH {
+            let read = self.read_bytes_into(addr + bytes.len(), &mut chunk);
+            match chunk[..read].iter().position(|byte| *byte == 0) {
+                Some(end) => {
+                    bytes.extend_from_slice(&chunk[..end]);
+                    break;
                 }
-                None => break,
+                None => bytes.extend_from_slice(&chunk[..read]),
+            }
+
+            if read < chunk.len() {
+                break;
             }
         }
-        Some(string)
+
+        bytes.truncate(MAX_C_STRING_LENGTH);
+        bytes
+    }
+
+    // This methos will throw and error if the address is not readable
+    pub fn maybe_read_ascii_string(&self, addr: usize) -> Option<String> {
+        Some(decode_c_string(self.read_c_string_bytes(addr)))
     }
 
     // This method is optimistic, and will return a cutted string if the address
     // is not readable
     pub fn read_ascii_string(&self, addr: usize) -> String {
-        let mut string = String::new();
-        let mut index = 0;
-        loop {
-            let val = self.maybe_read_u8(addr + index);
-            match val {
-                Some(val) => {
-                    if val == 0 || index > 1024 {
-                        break;
-                    }
-                    string.push(val as char);
-                    index += 1;
-                }
-                None => break,
-            }
-        }
-        string
+        decode_c_string(self.read_c_string_bytes(addr))
     }
 
     pub fn read_ptr_u8(&self, addr: usize) -> u8 {
@@ -501,6 +1046,19 @@ impl MonoReader {
         self.read_i64(ptr)
     }
 
+    // Element count of the MonoArray at array_ptr, None if unreadable.
+    // Arrays of more than one dimension have bounds and are read flat.
+    pub fn read_array_length(&self, array_ptr: usize) -> Option<u32> {
+        if self.maybe_read_ptr(array_ptr + constants::ARRAY_BOUNDS)? != 0 {
+            eprintln!(
+                "Multi-dimensional array at {:x}, reading it flat",
+                array_ptr
+            );
+        }
+
+        self.maybe_read_u32(array_ptr + constants::ARRAY_MAX_LENGTH)
+    }
+
     pub fn read_ptr_ascii_string(&self, addr: usize) -> String {
         let ptr = self.read_ptr(addr);
         self.read_ascii_string(ptr)
@@ -511,3 +1069,21 @@ impl MonoReader {
         self.read_ptr(ptr)
     }
 }
+
+// The addresses are only set by the &mut self init methods, and the only
+// state changed through &self is the field cache behind its Mutex and the
+// failed_reads AtomicU64, so reads through &self can run from any thread.
+// The cache is cleared in read_assembly_image, which takes &mut self. On
+// Linux and macOS the handle is a plain pid/port and the auto traits apply;
+// on Windows it is a raw HANDLE that is only passed to ReadProcessMemory,
+// which accepts it concurrently.
+#[cfg(target_os = "windows")]
+unsafe impl Send for MonoReader {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for MonoReader {}
+
+impl Drop for MonoReader {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
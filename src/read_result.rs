@@ -0,0 +1,47 @@
+use serde_json::{json, Map, Value};
+
+// Typed result of reading a path, for Rust users of the crate. The napi
+// functions keep returning plain JSON, see to_json.
+#[derive(Debug, PartialEq)]
+pub enum ReadResult {
+    // numbers, booleans, strings and null
+    Primitive(Value),
+    // fields of a class or struct
+    Object(Map<String, Value>),
+    Array(Vec<Value>),
+    // (key, value) pairs of a Dictionary, in entries order
+    Dictionary(Vec<(Value, Value)>),
+    Error(String),
+}
+
+impl ReadResult {
+    pub fn to_json(&self) -> Value {
+        match self {
+            ReadResult::Primitive(value) => value.clone(),
+            ReadResult::Object(fields) => Value::Object(fields.clone()),
+            ReadResult::Array(elements) => Value::Array(elements.clone()),
+            ReadResult::Dictionary(pairs) => Value::Array(
+                pairs
+                    .iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect(),
+            ),
+            ReadResult::Error(error) => json!({ "error": error }),
+        }
+    }
+}
+
+// Classifies the JSON produced by the readers, errors are reported as
+// { "error": ... } objects
+impl From<Value> for ReadResult {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Object(fields) => match fields.get("error") {
+                Some(Value::String(error)) if fields.len() == 1 => ReadResult::Error(error.clone()),
+                _ => ReadResult::Object(fields),
+            },
+            Value::Array(elements) => ReadResult::Array(elements),
+            value => ReadResult::Primitive(value),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// Searches the objects reachable from an object for the fields matching a
+// predicate, to find where the game keeps a value without chasing the
+// pointers by hand. Matches are given as the field names from the start
+// object joined by dots.
+use std::collections::HashSet;
+
+use crate::field_definition::FieldDefinition;
+use crate::type_code::TypeCode;
+use crate::{live_object_class, MonoReader};
+
+// Objects visited at most by one search, the managers the game hangs its
+// state from reach a lot of them in a few levels
+const MAX_SEARCHED_OBJECTS: usize = 10_000;
+
+struct Search<'a, F> {
+    reader: &'a MonoReader,
+    predicate: F,
+    // objects already searched, the graph has cycles (parent links,
+    // events holding their owner)
+    visited: HashSet<usize>,
+    paths: Vec<String>,
+}
+
+// The predicate gets each instance field with its address, max_depth is how
+// many references are followed from the object at location
+pub fn search_graph<F>(
+    reader: &MonoReader,
+    location: usize,
+    max_depth: u32,
+    predicate: F,
+) -> Vec<String>
+where
+    F: Fn(&FieldDefinition, usize) -> bool,
+{
+    let mut search = Search {
+        reader,
+        predicate,
+        visited: HashSet::new(),
+        paths: Vec::new(),
+    };
+
+    let ptr = reader.maybe_read_ptr(location).unwrap_or(0);
+    search.search_object(ptr, "", max_depth);
+
+    search.paths
+}
+
+impl<F> Search<'_, F>
+where
+    F: Fn(&FieldDefinition, usize) -> bool,
+{
+    fn search_object(&mut self, ptr: usize, path: &str, depth: u32) {
+        if ptr == 0 || self.visited.len() >= MAX_SEARCHED_OBJECTS || !self.visited.insert(ptr) {
+            return;
+        }
+
+        let class = match live_object_class(self.reader, ptr) {
+            Some(class) => class,
+            None => return,
+        };
+
+        for field in class.get_fields() {
+            let field_def = FieldDefinition::new(field, self.reader);
+            if field_def.type_info.is_static || field_def.type_info.is_const {
+                continue;
+            }
+
+            let address = class.field_address(&field_def, ptr);
+            let field_path = if path.is_empty() {
+                field_def.name.clone()
+            } else {
+                format!("{}.{}", path, field_def.name)
+            };
+
+            if (self.predicate)(&field_def, address) {
+                self.paths.push(field_path.clone());
+            }
+
+            let is_reference = matches!(
+                field_def.type_info.clone().code(),
+                TypeCode::CLASS | TypeCode::GENERICINST | TypeCode::OBJECT
+            );
+            if depth > 0 && is_reference {
+                let child = self.reader.maybe_read_ptr(address).unwrap_or(0);
+                self.search_object(child, &field_path, depth - 1);
+            }
+        }
+    }
+}
@@ -1,9 +1,35 @@
-use crate::field_definition::FieldDefinition;
+use crate::field_definition::{matches_field_name, FieldDefinition};
 use crate::managed::Managed;
+use crate::metadata::Metadata;
 use crate::mono_class_kind::{match_class_kind, MonoClassKind};
 use crate::{constants, MonoReader, TypeCode, TypeInfo};
 
 use core::fmt;
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum StaticValueError {
+    // the class has no static field with that name
+    FieldNotFound,
+    // the class has not been initialized yet, its statics are not allocated
+    VTableNotReady,
+}
+
+impl fmt::Display for StaticValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaticValueError::FieldNotFound => write!(f, "Static field not found"),
+            StaticValueError::VTableNotReady => write!(f, "Class not yet initialized, retry"),
+        }
+    }
+}
+
+// Static fields singletons usually keep their instance in, properties match
+// their backing field too
+const SINGLETON_FIELD_NAMES: [&str; 4] = ["Instance", "_instance", "instance", "s_instance"];
+
+// Bound of the parent chain walked by is_instance
+const MAX_PARENT_DEPTH: usize = 32;
 
 pub struct TypeDefinition<'a> {
     reader: &'a MonoReader,
@@ -20,10 +46,21 @@ pub struct TypeDefinition<'a> {
     pub v_table_size: i32,
     pub type_info: TypeInfo,
     pub class_kind: MonoClassKind,
+    // the generic type definition of a GInst class, 0 for other kinds
+    pub generic_definition_addr: usize,
     pub is_enum: bool,
     pub is_value_type: bool,
     pub generic_type_args: Vec<TypeInfo>,
     pub fields_base: usize,
+    // levels of nested objects expanded by Display
+    pub depth: u32,
+    // Display stops after this many fields
+    pub max_fields: Option<u32>,
+    // Display leaves out the fields stored behind a pointer
+    pub skip_pointers: bool,
+    // Display reads this many entries of the List and Dictionary fields
+    // inline instead of leaving them as objects
+    pub max_collection_entries: Option<u32>,
 }
 
 impl<'a> TypeDefinition<'a> {
@@ -35,8 +72,28 @@ impl<'a> TypeDefinition<'a> {
 
         let is_value_type = (bit_fields & 0x4) == 0x4;
 
-        let field_count =
-            reader.read_i32(definition_addr + constants::TYPE_DEFINITION_FIELD_COUNT as usize);
+        let class_kind_value =
+            reader.read_u8(definition_addr + constants::TYPE_DEFINITION_CLASS_KIND as usize);
+        let class_kind = match_class_kind(class_kind_value);
+
+        // Generic instances (MonoClassGenericInst) have no MonoClassDef part,
+        // their field count lives in the generic type definition they inflate
+        let generic_definition_addr = match class_kind {
+            MonoClassKind::GInst => {
+                let mono_generic_class = reader.read_ptr(
+                    definition_addr + constants::TYPE_DEFINITION_MONO_GENERIC_CLASS as usize,
+                );
+                reader.read_ptr(mono_generic_class)
+            }
+            _ => 0,
+        };
+
+        let field_count = match class_kind {
+            MonoClassKind::GInst if generic_definition_addr != 0 => reader.read_i32(
+                generic_definition_addr + constants::TYPE_DEFINITION_FIELD_COUNT as usize,
+            ),
+            _ => reader.read_i32(definition_addr + constants::TYPE_DEFINITION_FIELD_COUNT as usize),
+        };
 
         let nested_in_addr =
             reader.read_ptr(definition_addr + constants::TYPE_DEFINITION_NESTED_IN as usize);
@@ -74,44 +131,8 @@ impl<'a> TypeDefinition<'a> {
             &reader,
         );
 
-        let class_kind_value =
-            reader.read_u8(definition_addr + constants::TYPE_DEFINITION_CLASS_KIND as usize);
-        let class_kind = match_class_kind(class_kind_value);
-
         // Get the generic type arguments
-        let mut generic_type_args = Vec::new();
-        let code = type_info.clone().code();
-
-        match code {
-            TypeCode::GENERICINST => {
-                let mono_generic_class_address = type_info.clone().data;
-                let mono_class_address = reader.read_ptr(mono_generic_class_address);
-                // this.Image.GetTypeDefinition(mono_class_address);
-
-                let mono_generic_container_ptr =
-                    mono_class_address + constants::TYPE_DEFINITION_GENERIC_CONTAINER as usize;
-                let mono_generic_container_address = reader.read_ptr(mono_generic_container_ptr);
-
-                let mono_generic_context_ptr = mono_generic_class_address + constants::SIZE_OF_PTR;
-                let mono_generic_ins_ptr = reader.read_ptr(mono_generic_context_ptr);
-
-                // var argument_count = this.Process.ReadInt32(mono_generic_ins_ptr + 0x4);
-                let argument_count =
-                    reader.read_u32(mono_generic_container_address + (4 * constants::SIZE_OF_PTR));
-                let type_arg_v_ptr = mono_generic_ins_ptr + 0x8;
-
-                for i in 0..argument_count {
-                    let generic_type_argument_ptr =
-                        reader.read_ptr(type_arg_v_ptr + (i as usize * constants::SIZE_OF_PTR));
-                    let t = TypeInfo::new(generic_type_argument_ptr, reader);
-
-                    // println!(" {}: {}", i, t.clone().code());
-
-                    generic_type_args.push(t);
-                }
-            }
-            _ => {}
-        }
+        let generic_type_args = type_info.generic_type_args(reader);
 
         let fields_base = definition_addr;
 
@@ -130,18 +151,89 @@ impl<'a> TypeDefinition<'a> {
             v_table_size,
             type_info,
             class_kind,
+            generic_definition_addr,
             is_enum,
             is_value_type,
             generic_type_args,
             fields_base,
+            depth: 0,
+            max_fields: None,
+            skip_pointers: false,
+            max_collection_entries: None,
+        }
+    }
+
+    // Size of an instance, for a value type the size of the value once the
+    // object header is taken out, the stride of a struct array
+    pub fn value_size(&self) -> usize {
+        let instance_size = self
+            .reader
+            .read_i32(self.address + constants::TYPE_DEFINITION_INSTANCE_SIZE as usize)
+            .max(0) as usize;
+
+        if self.is_value_type {
+            instance_size.saturating_sub(constants::OBJECT_HEADER_SIZE)
+        } else {
+            instance_size
+        }
+    }
+
+    // The primitive an enum is stored as, the type of its value__ field
+    // which is its only instance field
+    pub fn enum_underlying_type(&self) -> Option<TypeInfo> {
+        if !self.is_enum {
+            return None;
+        }
+
+        self.get_fields()
+            .into_iter()
+            .map(|field| FieldDefinition::new(field, self.reader))
+            .find(|field| !field.type_info.is_static && !field.type_info.is_const)
+            .map(|field| field.type_info)
+    }
+
+    // "Namespace.Name", or just the name for types in the global namespace
+    pub fn full_name(&self) -> String {
+        if self.namespace_name.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace_name, self.name)
         }
     }
 
+    // Types declared inside this one (inner enums, structs and classes).
+    // Mono only links nested types to their outer class, so this scans the
+    // image's type definitions for the ones nested in this class.
+    pub fn get_nested_types(&self, defs: &[usize]) -> Vec<usize> {
+        defs.iter()
+            .copied()
+            .filter(|def| {
+                self.reader
+                    .maybe_read_ptr(def + constants::TYPE_DEFINITION_NESTED_IN as usize)
+                    == Some(self.address)
+            })
+            .collect()
+    }
+
+    // A generic type definition (List<T> itself, not List<int>) has unbound
+    // generic parameters, its field layout can't be used to read an object
+    pub fn is_open_generic(&self) -> bool {
+        matches!(self.class_kind, MonoClassKind::GTg)
+    }
+
     pub fn get_fields(&self) -> Vec<usize> {
-        let first_field = self
+        let mut first_field = self
             .reader
             .read_ptr(self.address + constants::TYPE_DEFINITION_FIELDS as usize);
 
+        // The inflated fields of a generic instance are only set up once the
+        // class is initialized, until then use the open definition layout
+        if first_field == 0 && self.generic_definition_addr != 0 {
+            first_field = self.reader.read_ptr(
+                self.generic_definition_addr + constants::TYPE_DEFINITION_FIELDS as usize,
+            );
+        }
+
         let mut fields = Vec::new();
 
         if first_field == 0 {
@@ -161,48 +253,365 @@ impl<'a> TypeDefinition<'a> {
         return fields;
     }
 
-    pub fn get_static_value(&self, field_name: &str) -> (usize, TypeInfo) {
-        // println!("get_static_value: {:?}", field_name);
-        let fields = self.get_fields();
-        for field in fields {
+    // Default values of the const fields (enum members included), read from
+    // the metadata of the image since Mono keeps them nowhere else
+    pub fn get_constant_values(&self) -> Vec<(String, Value)> {
+        self.read_metadata()
+            .map(|metadata| metadata.constant_values(self.type_token()))
+            .unwrap_or_default()
+    }
+
+    // Number of fields Mono lists for the class next to the number the
+    // metadata declares for it. They differ when the field list or field
+    // count offsets, or the size of MonoClassField, don't match the build.
+    pub fn check_field_count(&self) -> Option<(usize, u32)> {
+        let metadata_count = self.read_metadata()?.field_count(self.type_token())?;
+        let runtime_count = self.get_fields().len();
+
+        if runtime_count != metadata_count as usize {
+            eprintln!(
+                "Warning: {} has {} fields at runtime but {} in the metadata, the field offsets may not match this Unity version",
+                self.full_name(),
+                runtime_count,
+                metadata_count
+            );
+        }
+
+        Some((runtime_count, metadata_count))
+    }
+
+    fn type_token(&self) -> u32 {
+        self.reader
+            .read_u32(self.address + constants::TYPE_DEFINITION_TYPE_TOKEN as usize)
+    }
+
+    // The metadata of the image the class comes from
+    fn read_metadata(&self) -> Option<Metadata> {
+        let image = self
+            .reader
+            .read_ptr(self.address + constants::TYPE_DEFINITION_IMAGE as usize);
+        if image == 0 {
+            return None;
+        }
+
+        let raw_data = self.reader.read_ptr(image + constants::IMAGE_RAW_DATA);
+        let raw_data_len = self.reader.read_u32(image + constants::IMAGE_RAW_DATA_LEN) as usize;
+        if raw_data == 0 || raw_data_len > constants::MAX_IMAGE_SIZE {
+            return None;
+        }
+
+        self.reader
+            .read_bytes(raw_data, raw_data_len)
+            .and_then(Metadata::from_image)
+    }
+
+    pub fn get_constant_value(&self, field_name: &str) -> Option<Value> {
+        self.get_constant_values()
+            .into_iter()
+            .find(|(name, _)| matches_field_name(name, field_name))
+            .map(|(_, value)| value)
+    }
+
+    // Address of the slot after the vtable methods, it points to the static
+    // storage of the class shared by all its static fields. 0 while the
+    // vtable isn't created yet.
+    pub fn static_storage_base(&self) -> usize {
+        if self.v_table == 0 {
+            return 0;
+        }
+
+        let v_table_memory_size = constants::SIZE_OF_PTR * self.v_table_size as usize;
+        self.v_table + (constants::V_TABLE as usize) + v_table_memory_size
+    }
+
+    pub fn get_static_value(
+        &self,
+        field_name: &str,
+    ) -> Result<(usize, TypeInfo), StaticValueError> {
+        let field_def = match self.find_field(field_name) {
+            Some((_, field_def))
+                if !field_def.type_info.is_const && field_def.type_info.is_static =>
+            {
+                field_def
+            }
+            _ => return Err(StaticValueError::FieldNotFound),
+        };
+
+        // The vtable is only created once the class is initialized,
+        // which can take a while after the game starts
+        if self.v_table == 0 {
+            return Err(StaticValueError::VTableNotReady);
+        }
+
+        // Each field lives at its own offset inside the static storage.
+        // We return the location of the value: primitives are read inline
+        // from it, references hold the pointer to the object.
+        let static_data = self.reader.read_ptr(self.static_storage_base());
+
+        if static_data == 0 {
+            return Err(StaticValueError::VTableNotReady);
+        }
+
+        Ok((static_data + field_def.offset as usize, field_def.type_info))
+    }
+
+    // The object held by the static field of a singleton class, checking it
+    // really is an instance of the class so a wrong offset can't hand back
+    // an unrelated object
+    pub fn get_singleton_instance(&self) -> Option<usize> {
+        for field_name in SINGLETON_FIELD_NAMES {
+            let (location, _) = match self.get_static_value(field_name) {
+                Ok(value) => value,
+                Err(_e) => continue,
+            };
+
+            let instance = self.reader.maybe_read_ptr(location).unwrap_or(0);
+            if instance != 0 && self.is_instance(instance) {
+                return Some(instance);
+            }
+        }
+
+        None
+    }
+
+    // Whether the object at ptr is of this class or of a class deriving from it
+    pub fn is_instance(&self, ptr: usize) -> bool {
+        let vtable = self.reader.maybe_read_ptr(ptr).unwrap_or(0);
+        let mut class = match vtable {
+            0 => 0,
+            _ => self.reader.maybe_read_ptr(vtable).unwrap_or(0),
+        };
+
+        for _ in 0..MAX_PARENT_DEPTH {
+            if class == 0 {
+                return false;
+            }
+            if class == self.address {
+                return true;
+            }
+            class = self
+                .reader
+                .maybe_read_ptr(class + constants::TYPE_DEFINITION_PARENT as usize)
+                .unwrap_or(0);
+        }
+
+        false
+    }
+
+    // Finds the singleton-like instances held by "instance" static fields, as
+    // (field name, object address). Arrays and lists of instances are expanded
+    // to one entry per element.
+    pub fn get_static_instances(&self) -> Vec<(String, usize)> {
+        let mut instances = Vec::new();
+
+        for field in self.get_fields() {
             let field_def = FieldDefinition::new(field, self.reader);
-            if !field_def.type_info.is_const && field_def.type_info.is_static {
-                // let field_addr = field + field_def.offset as usize;
-                // println!("  {}: {:?}", field_def.name, field);
+            if field_def.type_info.is_const
+                || !field_def.type_info.is_static
+                || !field_def.name.to_lowercase().contains("instance")
+            {
+                continue;
+            }
 
-                if field_def.name == field_name {
-                    let v_table_memory_size = constants::SIZE_OF_PTR * self.v_table_size as usize;
+            let (location, type_info) = match self.get_static_value(&field_def.name) {
+                Ok(value) => value,
+                Err(_e) => continue,
+            };
 
-                    let value_ptr = self.reader.read_ptr(
-                        self.v_table + (constants::V_TABLE as usize) + v_table_memory_size,
-                    );
+            let ptr = self.reader.read_ptr(location);
+            if ptr == 0 {
+                continue;
+            }
 
-                    return (value_ptr, field_def.type_info);
+            match type_info.code() {
+                TypeCode::SZARRAY => {
+                    for element in self.read_array_elements(ptr, u32::MAX) {
+                        instances.push((field_def.name.clone(), element));
+                    }
                 }
+                _ => {
+                    // List<T> keeps its elements in _items, only _size are in use
+                    let managed = Managed::new(self.reader, location, None);
+                    let class = managed.read_class();
+                    let items = class.get_field("_items").0;
+
+                    if items != 0 && class.get_field("_size").0 != 0 {
+                        let size = self.reader.read_u32(class.get_value("_size", ptr).0);
+                        let items_ptr = self.reader.read_ptr(class.get_value("_items", ptr).0);
+                        for element in self.read_array_elements(items_ptr, size) {
+                            instances.push((field_def.name.clone(), element));
+                        }
+                    } else {
+                        instances.push((field_def.name.clone(), ptr));
+                    }
+                }
+            }
+        }
+
+        return instances;
+    }
+
+    // Reads the non null pointers stored in a managed array of references
+    fn read_array_elements(&self, array_ptr: usize, limit: u32) -> Vec<usize> {
+        let mut elements = Vec::new();
+        if array_ptr == 0 {
+            return elements;
+        }
+
+        let count = self
+            .reader
+            .read_array_length(array_ptr)
+            .unwrap_or(0)
+            .min(limit);
+
+        if count > constants::MAX_STATIC_INSTANCES {
+            eprintln!(
+                "Static instances array too big ({}), reading the first {}",
+                count,
+                constants::MAX_STATIC_INSTANCES
+            );
+        }
+
+        let start = array_ptr + constants::ARRAY_HEADER_SIZE;
+        for i in 0..count.min(constants::MAX_STATIC_INSTANCES) {
+            let element = self
+                .reader
+                .read_ptr(start + i as usize * constants::SIZE_OF_PTR);
+            if element != 0 {
+                elements.push(element);
             }
         }
-        return (0, TypeInfo::new(0, self.reader));
+
+        return elements;
     }
 
     pub fn get_field(&self, field_name: &str) -> (usize, TypeInfo) {
-        let fields = self.get_fields();
-        for field in fields {
+        match self.find_field(field_name) {
+            Some((field, field_def)) => (field, field_def.type_info),
+            None => (0, TypeInfo::new(0, self.reader)),
+        }
+    }
+
+    pub fn get_value(&self, field_name: &str, ptr: usize) -> (usize, TypeInfo) {
+        let def = match self.find_field(field_name) {
+            Some((_, field_def)) => field_def,
+            None => FieldDefinition::new(0, self.reader),
+        };
+
+        return (
+            self.field_address(&def, ptr),
+            self.resolve_generic_type(&def.type_info),
+        );
+    }
+
+    // Looks up a field by name, the reader remembers it so polling the same
+    // field again doesn't walk the field list
+    fn find_field(&self, field_name: &str) -> Option<(usize, FieldDefinition)> {
+        if let Some(cached) = self
+            .reader
+            .cached_field(self.address, &self.name, field_name)
+        {
+            return Some(cached);
+        }
+
+        for field in self.get_fields() {
             let field_def = FieldDefinition::new(field, self.reader);
-            let type_info = field_def.type_info.clone();
-            // let code = field_def.type_info.code();
-            // println!("  field: {}, {}", field_def.name, code);
-            if field_def.name == field_name {
-                return (field, type_info);
+            if matches_field_name(&field_def.name, field_name) {
+                if self.has_own_fields() {
+                    self.reader.cache_field(
+                        self.address,
+                        &self.name,
+                        field_name,
+                        field,
+                        &field_def,
+                    );
+                }
+                return Some((field, field_def));
             }
         }
-        return (0, TypeInfo::new(0, self.reader));
+
+        None
     }
 
-    pub fn get_value(&self, field_name: &str, ptr: usize) -> (usize, TypeInfo) {
-        let field = self.get_field(field_name);
-        let def = FieldDefinition::new(field.0, self.reader);
+    // False while a generic instance borrows the field list of its open
+    // definition, whose layout is replaced once the class is initialized
+    fn has_own_fields(&self) -> bool {
+        self.generic_definition_addr == 0
+            || self
+                .reader
+                .read_ptr(self.address + constants::TYPE_DEFINITION_FIELDS as usize)
+                != 0
+    }
+
+    // Fields typed with a generic parameter of the class (T of List<T>) are
+    // VAR types holding the parameter number, the concrete type is the
+    // matching type argument of this instance
+    pub fn resolve_generic_type(&self, type_info: &TypeInfo) -> TypeInfo {
+        if let TypeCode::VAR = type_info.clone().code() {
+            let number = self
+                .reader
+                .maybe_read_u16(type_info.data + constants::GENERIC_PARAM_NUM)
+                .map(|number| number as usize);
+
+            if let Some(argument) = number.and_then(|number| self.generic_type_args.get(number)) {
+                return argument.clone();
+            }
+        }
+
+        type_info.clone()
+    }
+
+    // Address of an instance field of the object at base. Field offsets count
+    // the object header, which unboxed value types don't have.
+    pub fn field_address(&self, field_def: &FieldDefinition, base: usize) -> usize {
+        let offset = if self.is_value_type {
+            field_def.offset - constants::OBJECT_HEADER_SIZE as i32
+        } else {
+            field_def.offset
+        };
+
+        (base as isize + offset as isize) as usize
+    }
+
+    // Resolves several fields of the object at ptr in a single pass over the
+    // class fields, in the same order as field_names
+    pub fn get_values(&self, field_names: &[String], ptr: usize) -> Vec<Option<(usize, TypeInfo)>> {
+        let mut values: Vec<Option<(usize, TypeInfo)>> = field_names
+            .iter()
+            .map(|name| {
+                self.reader
+                    .cached_field(self.address, &self.name, name)
+                    .map(|(_, field_def)| {
+                        (
+                            self.field_address(&field_def, ptr),
+                            self.resolve_generic_type(&field_def.type_info),
+                        )
+                    })
+            })
+            .collect();
+
+        if values.iter().all(|value| value.is_some()) {
+            return values;
+        }
+
+        let cache = self.has_own_fields();
+        for field in self.get_fields() {
+            let field_def = FieldDefinition::new(field, self.reader);
+            for (index, name) in field_names.iter().enumerate() {
+                if values[index].is_none() && matches_field_name(&field_def.name, name) {
+                    if cache {
+                        self.reader
+                            .cache_field(self.address, &self.name, name, field, &field_def);
+                    }
+                    values[index] = Some((
+                        self.field_address(&field_def, ptr),
+                        self.resolve_generic_type(&field_def.type_info),
+                    ));
+                }
+            }
+        }
 
-        return (def.offset as usize + ptr, def.type_info);
+        return values;
     }
 
     pub fn set_generic_type_args(&mut self, generic_type_args: Vec<TypeInfo>) {
@@ -212,47 +621,78 @@ impl<'a> TypeDefinition<'a> {
     pub fn set_fields_base(&mut self, addr: usize) {
         self.fields_base = addr;
     }
+
+    pub fn set_depth(&mut self, depth: u32) {
+        self.depth = depth;
+    }
+
+    pub fn set_max_fields(&mut self, max_fields: Option<u32>) {
+        self.max_fields = max_fields;
+    }
+
+    pub fn set_skip_pointers(&mut self, skip_pointers: bool) {
+        self.skip_pointers = skip_pointers;
+    }
+
+    pub fn set_max_collection_entries(&mut self, max_collection_entries: Option<u32>) {
+        self.max_collection_entries = max_collection_entries;
+    }
 }
 
 impl fmt::Display for TypeDefinition<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_open_generic() {
+            let error = format!(
+                "{} is an open generic definition, read the instance through its vtable class",
+                self.full_name()
+            );
+            return write!(f, "{{ \"error\": {} }}", serde_json::Value::String(error));
+        }
+
         let mut fields_str: Vec<String> = Vec::new();
 
         for _field in self.get_fields() {
+            if let Some(max_fields) = self.max_fields {
+                if fields_str.len() >= max_fields as usize {
+                    break;
+                }
+            }
+
             let field_def = FieldDefinition::new(_field, &self.reader);
             if !field_def.type_info.clone().is_const && !field_def.type_info.clone().is_static {
-                let code = field_def.type_info.clone().code();
-
-                let offset_a = field_def.offset;
+                let type_info = self.resolve_generic_type(&field_def.type_info);
+                let code = type_info.clone().code();
 
-                let offset_b = field_def.offset - (constants::SIZE_OF_PTR as i32 * 2);
+                if self.skip_pointers && type_info.is_reference(self.reader) {
+                    continue;
+                }
 
-                let offset = if self.is_value_type {
-                    offset_b
-                } else {
-                    offset_a
+                let managed = Managed::new(
+                    &self.reader,
+                    self.field_address(&field_def, self.fields_base),
+                    None,
+                );
+
+                let is_reference = matches!(
+                    code,
+                    TypeCode::CLASS | TypeCode::GENERICINST | TypeCode::OBJECT
+                );
+                let collection = match self.max_collection_entries {
+                    Some(limit) if is_reference => managed.read_collection_preview(limit),
+                    _ => None,
                 };
 
-                let managed = Managed::new(&self.reader, self.fields_base + offset as usize, None);
-
-                let val = match code {
-                    TypeCode::BOOLEAN => managed.read_boolean().to_string(),
-                    TypeCode::U4 => managed.read_u4().to_string(),
-                    TypeCode::U => managed.read_u4().to_string(),
-                    TypeCode::R4 => managed.read_r4().to_string(),
-                    TypeCode::R8 => managed.read_r8().to_string(),
-                    TypeCode::I4 => managed.read_i4().to_string(),
-                    TypeCode::I => managed.read_i4().to_string(),
-                    TypeCode::I2 => managed.read_i2().to_string(),
-                    TypeCode::U2 => managed.read_u2().to_string(),
-                    TypeCode::STRING => format!("\"{}\"", managed.read_string().to_string()),
-                    TypeCode::VALUETYPE => managed.read_valuetype().to_string(),
-                    _ => "null".to_string(),
+                let val = match collection {
+                    Some(collection) => collection,
+                    None if is_reference && self.depth > 0 => {
+                        managed.read_instance_field(&type_info, self.depth - 1)
+                    }
+                    None => managed.read_value(type_info),
                 };
 
                 // println!(
                 //     " - {} {} {} => {} {} {}",
-                //     self.fields_base + offset as usize,
+                //     self.field_address(&field_def, self.fields_base),
                 //     field_def.name,
                 //     field_def.type_info.clone().is_const,
                 //     field_def.type_info.clone().is_static,
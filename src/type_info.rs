@@ -1,6 +1,7 @@
 use crate::{constants, MonoReader};
 
 use crate::type_code::TypeCode;
+use crate::type_definition::TypeDefinition;
 
 #[derive(Clone)]
 pub struct TypeInfo {
@@ -15,10 +16,10 @@ pub struct TypeInfo {
 impl TypeInfo {
     pub fn new(addr: usize, reader: &MonoReader) -> Self {
         let data = reader.read_ptr(addr);
-        let attrs = reader.read_u32(addr + constants::SIZE_OF_PTR);
-        let is_static = (attrs & 0x10) == 0x10;
-        let is_const = (attrs & 0x40) == 0x40;
-        let type_code = 0xff & (attrs >> 16);
+        let attrs = reader.read_u32(addr + constants::TYPE_ATTRS);
+        let is_static = (attrs & constants::FIELD_ATTRIBUTE_STATIC) != 0;
+        let is_const = (attrs & constants::FIELD_ATTRIBUTE_LITERAL) != 0;
+        let type_code = 0xff & (attrs >> constants::TYPE_CODE_SHIFT);
 
         TypeInfo {
             addr,
@@ -30,6 +31,113 @@ impl TypeInfo {
         }
     }
 
+    // A type known only by its type code, like the key and value types given
+    // by name to read a dictionary
+    pub fn from_code(type_code: u32) -> Self {
+        TypeInfo {
+            addr: 0,
+            data: 0,
+            attrs: type_code << constants::TYPE_CODE_SHIFT,
+            is_static: false,
+            is_const: false,
+            type_code,
+        }
+    }
+
+    // Reads the type arguments of a GENERICINST type, empty for any other type
+    pub fn generic_type_args(&self, reader: &MonoReader) -> Vec<TypeInfo> {
+        let mut generic_type_args = Vec::new();
+
+        match self.clone().code() {
+            TypeCode::GENERICINST => {
+                let mono_generic_class_address = self.data;
+                let mono_class_address = reader.read_ptr(mono_generic_class_address);
+                // this.Image.GetTypeDefinition(mono_class_address);
+
+                let mono_generic_container_ptr =
+                    mono_class_address + constants::TYPE_DEFINITION_GENERIC_CONTAINER as usize;
+                let mono_generic_container_address = reader.read_ptr(mono_generic_container_ptr);
+
+                let mono_generic_context_ptr = mono_generic_class_address + constants::SIZE_OF_PTR;
+                let mono_generic_ins_ptr = reader.read_ptr(mono_generic_context_ptr);
+
+                // type_argc of the generic definition
+                let container_argument_count =
+                    reader.read_u32(mono_generic_container_address + (4 * constants::SIZE_OF_PTR));
+
+                // type_argc of the instantiation, a 22 bit field after the id
+                let instance_argument_count = reader
+                    .read_u32(mono_generic_ins_ptr + constants::GENERIC_INST_TYPE_ARGC)
+                    & 0x3fffff;
+
+                let mut argument_count = container_argument_count;
+
+                if container_argument_count != instance_argument_count {
+                    eprintln!(
+                        "Generic argument count mismatch: container {} instance {}",
+                        container_argument_count, instance_argument_count
+                    );
+                    argument_count = container_argument_count.min(instance_argument_count);
+                }
+
+                // Anything above the bound means we are not looking at a
+                // generic container, better no arguments than bogus ones
+                if argument_count > constants::MAX_GENERIC_ARGUMENTS {
+                    eprintln!("Generic argument count out of bounds: {}", argument_count);
+                    return generic_type_args;
+                }
+
+                let type_arg_v_ptr = mono_generic_ins_ptr + constants::GENERIC_INST_TYPE_ARGV;
+
+                for i in 0..argument_count {
+                    let generic_type_argument_ptr =
+                        reader.read_ptr(type_arg_v_ptr + (i as usize * constants::SIZE_OF_PTR));
+                    let t = TypeInfo::new(generic_type_argument_ptr, reader);
+
+                    generic_type_args.push(t);
+                }
+            }
+            _ => {}
+        }
+
+        generic_type_args
+    }
+
+    // Whether a field of this type holds a pointer to an object rather than
+    // the value itself. A GENERICINST is a reference unless its generic
+    // definition is a struct (KeyValuePair, Nullable, ...)
+    pub fn is_reference(&self, reader: &MonoReader) -> bool {
+        match self.clone().code() {
+            TypeCode::CLASS
+            | TypeCode::STRING
+            | TypeCode::OBJECT
+            | TypeCode::SZARRAY
+            | TypeCode::ARRAY => true,
+            TypeCode::GENERICINST => {
+                !matches!(self.resolve_class(reader), Some(class) if class.is_value_type)
+            }
+            _ => false,
+        }
+    }
+
+    // The class the type refers to: the class itself for CLASS and
+    // VALUETYPE, the generic definition for GENERICINST and the element
+    // class for SZARRAY. None for primitives and unresolved types.
+    pub fn resolve_class<'a>(&self, reader: &'a MonoReader) -> Option<TypeDefinition<'a>> {
+        let class = match self.clone().code() {
+            TypeCode::CLASS | TypeCode::VALUETYPE | TypeCode::SZARRAY => self.data,
+            // MonoGenericClass, container_class first
+            TypeCode::GENERICINST => reader.maybe_read_ptr(self.data)?,
+            _ => return None,
+        };
+
+        if class == 0 {
+            return None;
+        }
+
+        Some(TypeDefinition::new(class, reader))
+    }
+
     pub fn code(self) -> TypeCode {
         // return the appropiate TypeCode enum based on self.type_code
         match self.type_code {
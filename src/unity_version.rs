@@ -0,0 +1,101 @@
+use core::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Files of <Game>_Data that start with the Unity version in their header
+const VERSION_FILES: [&str; 3] = ["globalgamemanagers", "data.unity3d", "mainData"];
+
+// The version string sits in the first few bytes of the header
+const HEADER_READ_SIZE: u64 = 0x1000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnityVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    // a (alpha), b (beta), f (final), p (patch)
+    pub release_type: char,
+    pub build: u32,
+}
+
+impl UnityVersion {
+    // Parses a version like "2021.3.14f1"
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.split('.');
+
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let last = parts.next()?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let release_index = last.find(|c: char| !c.is_ascii_digit())?;
+        let patch = last[..release_index].parse().ok()?;
+        let release_type = last[release_index..].chars().next()?;
+
+        if !['a', 'b', 'f', 'p'].contains(&release_type) {
+            return None;
+        }
+
+        let build = last[release_index + 1..].parse().ok()?;
+
+        Some(UnityVersion {
+            major,
+            minor,
+            patch,
+            release_type,
+            build,
+        })
+    }
+}
+
+impl fmt::Display for UnityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}{}{}",
+            self.major, self.minor, self.patch, self.release_type, self.build
+        )
+    }
+}
+
+// Finds the first version string embedded in a serialized file or bundle header
+pub fn find_version_in_header(bytes: &[u8]) -> Option<UnityVersion> {
+    bytes
+        .split(|b| !(b.is_ascii_alphanumeric() || *b == b'.'))
+        .filter_map(|token| std::str::from_utf8(token).ok())
+        .find_map(UnityVersion::parse)
+}
+
+// Reads the Unity version from the game data files next to the executable,
+// <Game>_Data/globalgamemanagers or data.unity3d
+pub fn detect_unity_version(exe_path: &str) -> Option<UnityVersion> {
+    let exe_path = Path::new(exe_path);
+    let game_name = exe_path.file_stem()?.to_str()?;
+    let data_dir = exe_path.parent()?.join(format!("{}_Data", game_name));
+
+    for file_name in VERSION_FILES {
+        let file = match File::open(data_dir.join(file_name)) {
+            Ok(file) => file,
+            Err(_e) => continue,
+        };
+
+        let mut header = Vec::new();
+        if file
+            .take(HEADER_READ_SIZE)
+            .read_to_end(&mut header)
+            .is_err()
+        {
+            continue;
+        }
+
+        if let Some(version) = find_version_in_header(&header) {
+            return Some(version);
+        }
+    }
+
+    None
+}